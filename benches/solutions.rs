@@ -0,0 +1,137 @@
+use aoc2024::{
+    day1,
+    load::{input_path, InputFile},
+    solutions, sort,
+};
+use criterion::{black_box, criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion};
+
+/// Benchmarks every registered solution that we have an input for, timing the parser and each part
+/// separately. Alternate variants of a day share a group, so they can be compared against each
+/// other.
+pub fn bench_solutions(c: &mut Criterion) {
+    for solution in solutions() {
+        let day = solution.day();
+        let Ok(file) = InputFile::day(day) else {
+            eprintln!("Skipping day {day}: no input at {}", input_path(day));
+            continue;
+        };
+        let Ok(input) = file.text() else {
+            eprintln!("Skipping day {day}: input is not valid UTF-8");
+            continue;
+        };
+
+        let parsed = match solution.parse(input) {
+            Ok(parsed) => parsed,
+            Err(e) => {
+                eprintln!("Skipping day {day}: {e}");
+                continue;
+            }
+        };
+
+        let mut group = c.benchmark_group(format!("day{day:02}"));
+        let variant = solution.variant();
+
+        group.bench_function(BenchmarkId::new("parse", variant), |b| {
+            b.iter(|| solution.parse(black_box(input)))
+        });
+        group.bench_function(BenchmarkId::new("part1", variant), |b| {
+            b.iter(|| parsed.part1())
+        });
+        group.bench_function(BenchmarkId::new("part2", variant), |b| {
+            b.iter(|| parsed.part2())
+        });
+
+        group.finish();
+    }
+}
+
+/// Compares sorting day 1's location IDs by comparison against sorting them by radix, without
+/// the parsing around it.
+pub fn bench_sorts(c: &mut Criterion) {
+    let Ok(file) = InputFile::day(1) else {
+        eprintln!("Skipping sorts: no input at {}", input_path(1));
+        return;
+    };
+    let ids = file
+        .text()
+        .unwrap_or_default()
+        .split_ascii_whitespace()
+        .filter_map(|id| id.parse().ok())
+        .collect::<Vec<u32>>();
+
+    let mut group = c.benchmark_group("sort");
+    group.bench_function("sort_unstable", |b| {
+        b.iter_batched_ref(
+            || ids.clone(),
+            |ids| ids.sort_unstable(),
+            BatchSize::SmallInput,
+        )
+    });
+    group.bench_function("radix_sort", |b| {
+        b.iter_batched_ref(
+            || ids.clone(),
+            |ids| sort::radix_sort(ids),
+            BatchSize::SmallInput,
+        )
+    });
+    group.finish();
+}
+
+/// Generates `n` location IDs of up to 5 digits, with a simple xorshift so runs are repeatable.
+fn location_ids(n: usize, mut seed: u64) -> Vec<u32> {
+    (0..n)
+        .map(|_| {
+            seed ^= seed << 13;
+            seed ^= seed >> 7;
+            seed ^= seed << 17;
+            (seed % 100_000) as u32
+        })
+        .collect()
+}
+
+/// Compares the ways of working out day 1's similarity score on generated lists of each size,
+/// both shuffled and already sorted, counting the sorting against the strategy that needs it.
+pub fn bench_similarity(c: &mut Criterion) {
+    let mut group = c.benchmark_group("day01_similarity");
+
+    for n in [100, 1_000, 10_000, 100_000] {
+        let left = location_ids(n, 1);
+        // Draw the right list from fewer IDs, so plenty of them match.
+        let right = location_ids(n, 2)
+            .iter()
+            .map(|id| id % 1_000)
+            .collect::<Vec<_>>();
+
+        let (mut left_sorted, mut right_sorted) = (left.clone(), right.clone());
+        left_sorted.sort_unstable();
+        right_sorted.sort_unstable();
+
+        for (order, left, right) in [
+            ("unsorted", &left, &right),
+            ("presorted", &left_sorted, &right_sorted),
+        ] {
+            group.bench_function(BenchmarkId::new(format!("merge/{order}"), n), |b| {
+                b.iter_batched_ref(
+                    || (left.clone(), right.clone()),
+                    |(left, right)| {
+                        left.sort_unstable();
+                        right.sort_unstable();
+                        day1::similarity_sorted(left, right)
+                    },
+                    BatchSize::SmallInput,
+                )
+            });
+            group.bench_function(BenchmarkId::new(format!("hashmap/{order}"), n), |b| {
+                b.iter(|| day1::similarity_hashed(black_box(left), black_box(right)))
+            });
+            group.bench_function(BenchmarkId::new(format!("dense/{order}"), n), |b| {
+                b.iter(|| day1::similarity_dense(black_box(left), black_box(right)))
+            });
+        }
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_solutions, bench_sorts, bench_similarity);
+criterion_main!(benches);
@@ -0,0 +1,176 @@
+//! Known-good answers for our puzzle inputs, so refactors can be checked against them.
+//!
+//! Answers live in a small subset of TOML, with a table per day and a key per part:
+//!
+//! ```toml
+//! [day1]
+//! part1 = "1234"
+//! part2 = 5678
+//! ```
+//!
+//! Comments at the top of the file are kept when it's written back, but any further down are not.
+
+use std::collections::BTreeMap;
+use std::fmt;
+
+/// Where `verify` looks for answers by default.
+pub const DEFAULT_PATH: &str = "answers/2024.toml";
+
+#[derive(Debug, Default, PartialEq)]
+pub struct Answers {
+    /// The comment lines before the first table, as they were.
+    header: Vec<String>,
+    answers: BTreeMap<(u8, u8), String>,
+}
+
+impl Answers {
+    pub fn parse(input: &str) -> Result<Answers, String> {
+        let mut header = Vec::new();
+        let mut answers = BTreeMap::new();
+        let mut day = None;
+
+        for (n, line) in input.lines().enumerate() {
+            let line = line.trim();
+            let err = |msg: &str| format!("line {}: {msg}: '{line}'", n + 1);
+
+            if line.starts_with('#') && day.is_none() {
+                header.push(line.to_string());
+            }
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            if let Some(table) = line.strip_prefix('[') {
+                let table = table.strip_suffix(']').ok_or_else(|| err("unclosed table"))?;
+                day = Some(
+                    table
+                        .trim()
+                        .strip_prefix("day")
+                        .and_then(|d| d.parse::<u8>().ok())
+                        .ok_or_else(|| err("expected a table like [day1]"))?,
+                );
+                continue;
+            }
+
+            let Some(day) = day else {
+                return Err(err("answer outside a [dayN] table"));
+            };
+            let (key, value) = line
+                .split_once('=')
+                .ok_or_else(|| err("expected part1 = ..."))?;
+            let part = match key.trim() {
+                "part1" => 1,
+                "part2" => 2,
+                _ => return Err(err("expected part1 or part2")),
+            };
+            let value = value.trim();
+            let value = match value.strip_prefix('"') {
+                Some(quoted) => quoted
+                    .strip_suffix('"')
+                    .ok_or_else(|| err("unterminated string"))?,
+                None if !value.is_empty() && value.bytes().all(|b| b.is_ascii_digit()) => value,
+                None => return Err(err("expected an integer or a quoted string")),
+            };
+
+            if answers.insert((day, part), value.to_string()).is_some() {
+                return Err(err("duplicate answer"));
+            }
+        }
+
+        Ok(Answers { header, answers })
+    }
+
+    pub fn get(&self, day: u8, part: u8) -> Option<&str> {
+        self.answers.get(&(day, part)).map(String::as_str)
+    }
+
+    pub fn insert(&mut self, day: u8, part: u8, answer: String) {
+        self.answers.insert((day, part), answer);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.answers.is_empty()
+    }
+}
+
+impl fmt::Display for Answers {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut day = None;
+
+        for line in &self.header {
+            writeln!(f, "{line}")?;
+        }
+
+        for (&(d, part), answer) in &self.answers {
+            if day != Some(d) {
+                if day.is_some() || !self.header.is_empty() {
+                    writeln!(f)?;
+                }
+                writeln!(f, "[day{d}]")?;
+                day = Some(d);
+            }
+            writeln!(f, "part{part} = \"{answer}\"")?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_tables_and_values() {
+        let answers = Answers::parse(
+            "# comment
+
+[day1]
+part1 = \"11\"
+part2 = 31
+
+[ day2 ]
+part2 = \"4\"",
+        )
+        .unwrap();
+
+        assert_eq!(answers.get(1, 1), Some("11"));
+        assert_eq!(answers.get(1, 2), Some("31"));
+        assert_eq!(answers.get(2, 1), None);
+        assert_eq!(answers.get(2, 2), Some("4"));
+    }
+
+    #[test]
+    fn rejects_malformed_lines() {
+        assert!(Answers::parse("part1 = 1").is_err());
+        assert!(Answers::parse("[day1").is_err());
+        assert!(Answers::parse("[dayone]").is_err());
+        assert!(Answers::parse("[day1]\npart3 = 1").is_err());
+        assert!(Answers::parse("[day1]\npart1 = \"1").is_err());
+        assert!(Answers::parse("[day1]\npart1 = abc").is_err());
+        assert!(Answers::parse("[day1]\npart1 = 1\npart1 = 2").is_err());
+    }
+
+    #[test]
+    fn round_trips() {
+        let mut answers = Answers::default();
+        answers.insert(2, 1, "2".to_string());
+        answers.insert(1, 2, "31".to_string());
+        answers.insert(1, 1, "11".to_string());
+
+        assert_eq!(Answers::parse(&answers.to_string()), Ok(answers));
+    }
+
+    #[test]
+    fn keeps_the_header_comments() {
+        let mut answers = Answers::parse("# Expected answers.\n#\n# Check them first.\n").unwrap();
+        assert!(answers.is_empty());
+        answers.insert(1, 1, "11".to_string());
+
+        assert_eq!(
+            answers.to_string(),
+            "# Expected answers.\n#\n# Check them first.\n\n[day1]\npart1 = \"11\"\n"
+        );
+        assert_eq!(Answers::parse(&answers.to_string()), Ok(answers));
+    }
+}
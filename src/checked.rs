@@ -0,0 +1,117 @@
+//! Arithmetic for answers, which are all `u64`s so that even huge generated inputs fit. The one
+//! exception is day 1's similarity score for lists with negative IDs, which is an `i128`.
+//!
+//! Built with the `checked` feature, every sum and product that goes into an answer is checked,
+//! and overflowing gives an [`OverflowError`] naming the answer rather than wrapping round to a
+//! wrong one. Without it they wrap, as they always could in release builds. Counting things one
+//! at a time can't overflow a `u64` in practice, so counts aren't checked.
+
+use crate::OverflowError;
+
+/// The integer types answers are worked out in.
+pub(crate) trait Int: Copy + Default {
+    fn overflowing_add(self, other: Self) -> (Self, bool);
+    fn overflowing_mul(self, other: Self) -> (Self, bool);
+}
+
+macro_rules! int {
+    ($($t:ty),*) => {
+        $(
+            impl Int for $t {
+                fn overflowing_add(self, other: Self) -> (Self, bool) {
+                    <$t>::overflowing_add(self, other)
+                }
+
+                fn overflowing_mul(self, other: Self) -> (Self, bool) {
+                    <$t>::overflowing_mul(self, other)
+                }
+            }
+        )*
+    };
+}
+
+int!(u64, i128);
+
+/// An answer being worked out, named for the error if it overflows.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Answer {
+    day: u8,
+    what: &'static str,
+}
+
+impl Answer {
+    pub(crate) const fn new(day: u8, what: &'static str) -> Self {
+        Answer { day, what }
+    }
+
+    pub(crate) fn add<T: Int>(self, a: T, b: T) -> Result<T, OverflowError> {
+        self.check(a.overflowing_add(b))
+    }
+
+    pub(crate) fn mul<T: Int>(self, a: T, b: T) -> Result<T, OverflowError> {
+        self.check(a.overflowing_mul(b))
+    }
+
+    pub(crate) fn sum<T: Int>(
+        self,
+        values: impl IntoIterator<Item = T>,
+    ) -> Result<T, OverflowError> {
+        values.into_iter().try_fold(T::default(), |sum, v| self.add(sum, v))
+    }
+
+    fn check<T>(self, (value, overflowed): (T, bool)) -> Result<T, OverflowError> {
+        if cfg!(feature = "checked") && overflowed {
+            Err(OverflowError {
+                day: self.day,
+                what: self.what,
+            })
+        } else {
+            Ok(value)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TOTAL: Answer = Answer::new(9, "the total");
+
+    #[test]
+    fn adds_and_multiplies() {
+        assert_eq!(TOTAL.add(2_u64, 3), Ok(5));
+        assert_eq!(TOTAL.mul(2_u64, 3), Ok(6));
+        assert_eq!(TOTAL.sum([1_u64, 2, 3]), Ok(6));
+        assert_eq!(TOTAL.sum::<u64>([]), Ok(0));
+        assert_eq!(TOTAL.add(u64::MAX - 1, 1), Ok(u64::MAX));
+        assert_eq!(TOTAL.mul(-2_i128, 3), Ok(-6));
+        assert_eq!(TOTAL.sum([u64::MAX as i128, 1, -5]), Ok(u64::MAX as i128 - 4));
+    }
+
+    #[test]
+    fn overflows_only_when_checked() {
+        let overflow = OverflowError {
+            day: 9,
+            what: "the total",
+        };
+        let results = [
+            (TOTAL.add(u64::MAX, 1), 0),
+            (TOTAL.mul(1 << 32, 1 << 32), 0),
+            (TOTAL.sum([u64::MAX, 2]), 1),
+        ];
+        let signed = TOTAL.add(i128::MIN, -1);
+
+        for (result, wrapped) in results {
+            if cfg!(feature = "checked") {
+                assert_eq!(result, Err(overflow));
+            } else {
+                assert_eq!(result, Ok(wrapped));
+            }
+        }
+        if cfg!(feature = "checked") {
+            assert_eq!(signed, Err(overflow));
+        } else {
+            assert_eq!(signed, Ok(i128::MAX));
+        }
+    }
+}
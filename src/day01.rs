@@ -1,80 +1,763 @@
+use std::{fmt, hash::Hash};
+
 use aoc_runner_derive::{aoc, aoc_generator};
+use rustc_hash::FxHashMap as HashMap;
+use winnow::{
+    ascii::dec_uint,
+    combinator::separated_pair,
+    token::take_while,
+    PResult, Parser,
+};
+
+use crate::{
+    checked::Answer,
+    column_of,
+    grammar::{self, expect},
+    input,
+    join::{merge_join, Join},
+    number::{self, NumberError},
+    parse_field, sort, OverflowError, ParseError, Solution, SolveError,
+};
+
+#[path = "day01/live.rs"]
+pub mod live;
+
+pub struct Day1;
+
+impl Solution for Day1 {
+    const DAY: u8 = 1;
+
+    type Input<'a> = (Vec<u32>, Vec<u32>);
+    type Output1 = u64;
+    type Output2 = u64;
+
+    fn parse(input: &str) -> Result<Self::Input<'_>, ParseError> {
+        parse(input)
+    }
+
+    fn part1(input: &Self::Input<'_>) -> Result<u64, SolveError> {
+        Ok(part1_impl(input)?)
+    }
+
+    fn part2(input: &Self::Input<'_>) -> Result<u64, SolveError> {
+        Ok(part2_impl(input)?)
+    }
+}
+
+/// Decodes both columns with SWAR when every line has the same layout.
+pub struct Day1Swar;
+
+impl Solution for Day1Swar {
+    const DAY: u8 = 1;
+    const VARIANT: &'static str = "swar";
+
+    type Input<'a> = (Vec<u32>, Vec<u32>);
+    type Output1 = u64;
+    type Output2 = u64;
+
+    fn parse(input: &str) -> Result<Self::Input<'_>, ParseError> {
+        parse_swar(input)
+    }
+
+    fn part1(input: &Self::Input<'_>) -> Result<u64, SolveError> {
+        Ok(part1_impl(input)?)
+    }
+
+    fn part2(input: &Self::Input<'_>) -> Result<u64, SolveError> {
+        Ok(part2_impl(input)?)
+    }
+}
+
+/// Checks the input against a grammar, rejecting anything out of place.
+pub struct Day1Strict;
+
+impl Solution for Day1Strict {
+    const DAY: u8 = 1;
+    const VARIANT: &'static str = "strict";
+
+    type Input<'a> = (Vec<u32>, Vec<u32>);
+    type Output1 = u64;
+    type Output2 = u64;
+
+    fn parse(input: &str) -> Result<Self::Input<'_>, ParseError> {
+        parse_strict(input)
+    }
+
+    fn part1(input: &Self::Input<'_>) -> Result<u64, SolveError> {
+        Ok(part1_impl(input)?)
+    }
+
+    fn part2(input: &Self::Input<'_>) -> Result<u64, SolveError> {
+        Ok(part2_impl(input)?)
+    }
+}
+
+/// Sorts the lists with a radix sort rather than comparing the IDs.
+pub struct Day1Radix;
+
+impl Solution for Day1Radix {
+    const DAY: u8 = 1;
+    const VARIANT: &'static str = "radix";
+
+    type Input<'a> = (Vec<u32>, Vec<u32>);
+    type Output1 = u64;
+    type Output2 = u64;
+
+    fn parse(input: &str) -> Result<Self::Input<'_>, ParseError> {
+        parse_radix(input)
+    }
+
+    fn part1(input: &Self::Input<'_>) -> Result<u64, SolveError> {
+        Ok(part1_impl(input)?)
+    }
+
+    fn part2(input: &Self::Input<'_>) -> Result<u64, SolveError> {
+        Ok(part2_impl(input)?)
+    }
+}
+
+/// Leaves the lists unsorted, and counts the IDs in the right list in a hash map for part 2.
+pub struct Day1Hashed;
+
+impl Solution for Day1Hashed {
+    const DAY: u8 = 1;
+    const VARIANT: &'static str = "hashmap";
+
+    type Input<'a> = (Vec<u32>, Vec<u32>);
+    type Output1 = u64;
+    type Output2 = u64;
+
+    fn parse(input: &str) -> Result<Self::Input<'_>, ParseError> {
+        read_lists(input)
+    }
+
+    fn part1(input: &Self::Input<'_>) -> Result<u64, SolveError> {
+        Ok(part1_unsorted(input)?)
+    }
+
+    fn part2(input: &Self::Input<'_>) -> Result<u64, SolveError> {
+        Ok(part2_hashmap(input)?)
+    }
+}
+
+/// Leaves the lists unsorted, and counts the IDs in the right list in an array for part 2.
+pub struct Day1Dense;
+
+impl Solution for Day1Dense {
+    const DAY: u8 = 1;
+    const VARIANT: &'static str = "dense";
+
+    type Input<'a> = (Vec<u32>, Vec<u32>);
+    type Output1 = u64;
+    type Output2 = u64;
+
+    fn parse(input: &str) -> Result<Self::Input<'_>, ParseError> {
+        read_lists(input)
+    }
+
+    fn part1(input: &Self::Input<'_>) -> Result<u64, SolveError> {
+        Ok(part1_unsorted(input)?)
+    }
+
+    fn part2(input: &Self::Input<'_>) -> Result<u64, SolveError> {
+        Ok(part2_dense(input)?)
+    }
+}
+
+/// Reads the IDs as whatever integer type fits them, so lists with negative or huge IDs can be
+/// solved too.
+pub struct Day1Any;
+
+impl Solution for Day1Any {
+    const DAY: u8 = 1;
+    const VARIANT: &'static str = "any";
+
+    type Input<'a> = Lists;
+    type Output1 = u64;
+    type Output2 = i128;
+
+    fn parse(input: &str) -> Result<Self::Input<'_>, ParseError> {
+        Lists::parse(input)
+    }
+
+    fn part1(input: &Self::Input<'_>) -> Result<u64, SolveError> {
+        Ok(part1_any(input)?)
+    }
+
+    fn part2(input: &Self::Input<'_>) -> Result<i128, SolveError> {
+        Ok(part2_any(input)?)
+    }
+}
+
+/// An integer type that location IDs can be read as. The puzzle's IDs are small and unsigned, so
+/// the solutions use `u32`s, but other lists can have negative IDs, or ones too big for a `u32`.
+pub trait LocationId: Copy + Ord + Hash + fmt::Debug {
+    /// What the similarity score adds up to: a `u64` like the other answers, or an `i128` for
+    /// signed IDs, as their scores can be negative.
+    type Score: Copy + Default + PartialEq + fmt::Debug + fmt::Display + Into<i128>;
+
+    /// Reads an ID, reporting anything that isn't a number of this type.
+    fn parse(field: &[u8]) -> Result<Self, NumberError>;
+
+    /// How far apart two IDs are.
+    fn distance(self, other: Self) -> u64;
 
-use crate::parse_number;
+    /// Scores an ID that's in the left list `left` times, and the right list `right` times.
+    fn score(self, left: u64, right: u64) -> Result<Self::Score, OverflowError>;
+
+    /// Adds a score to the similarity score so far.
+    fn add_score(sum: Self::Score, score: Self::Score) -> Result<Self::Score, OverflowError>;
+}
+
+macro_rules! location_id {
+    ($($t:ty: $score:ty = $parse:path),*) => {
+        $(
+            impl LocationId for $t {
+                type Score = $score;
+
+                fn parse(field: &[u8]) -> Result<Self, NumberError> {
+                    $parse(field)
+                }
+
+                fn distance(self, other: Self) -> u64 {
+                    u64::from(self.abs_diff(other))
+                }
+
+                fn score(self, left: u64, right: u64) -> Result<$score, OverflowError> {
+                    let score = SIMILARITY.mul(<$score>::from(self), <$score>::from(left))?;
+                    SIMILARITY.mul(score, <$score>::from(right))
+                }
+
+                fn add_score(sum: $score, score: $score) -> Result<$score, OverflowError> {
+                    SIMILARITY.add(sum, score)
+                }
+            }
+        )*
+    };
+}
+
+location_id!(
+    u32: u64 = number::parse_checked,
+    u64: u64 = number::parse_checked,
+    i32: i128 = number::parse_signed,
+    i64: i128 = number::parse_signed
+);
 
 #[aoc_generator(day1)]
-fn parse(input: &str) -> (Vec<u32>, Vec<u32>) {
+fn parse(input: &str) -> Result<(Vec<u32>, Vec<u32>), ParseError> {
+    parse_lists(input)
+}
+
+/// Reads both lists of location IDs as `T`s, and sorts them.
+fn parse_lists<T: LocationId>(input: &str) -> Result<(Vec<T>, Vec<T>), ParseError> {
+    let (mut left, mut right) = read_lists(input)?;
+
+    left.sort_unstable();
+    right.sort_unstable();
+
+    Ok((left, right))
+}
+
+/// Reads both lists of location IDs, in the order they're given.
+fn read_lists<T: LocationId>(input: &str) -> Result<(Vec<T>, Vec<T>), ParseError> {
     let mut left = Vec::with_capacity(1000);
     let mut right = Vec::with_capacity(1000);
 
-    input.lines().for_each(|line| {
+    for (n, line) in input::lines(input).enumerate() {
         // Just doing val.parse().unwrap() would be simpler.
         // But here we play fast and loose with number parsing.
-        // We know our inputs are all positive. So we skip error handling, sign handling and other stuff this way.
-        let mut parts = line.split_ascii_whitespace().map(parse_number);
-        left.push(unsafe { parts.next().unwrap_unchecked() });
-        right.push(unsafe { parts.next().unwrap_unchecked() });
-    });
+        // The puzzle's IDs are all positive. So unsigned IDs skip sign handling and other stuff
+        // this way, only checking that each field is made of digits and fits.
+        let mut parts = line.split_ascii_whitespace();
+        let mut next = |expected| {
+            let field = parts
+                .next()
+                .ok_or_else(|| ParseError::new(1, n + 1, line.len() + 1, "", expected))?;
+            T::parse(field.as_bytes())
+                .map_err(|_| ParseError::new(1, n + 1, column_of(line, field), field, expected))
+        };
+        left.push(next("a location ID")?);
+        right.push(next("a second location ID")?);
+
+        if let Some(extra) = parts.next() {
+            return Err(ParseError::new(
+                1,
+                n + 1,
+                column_of(line, extra),
+                extra,
+                "the end of the line",
+            ));
+        }
+    }
+
+    Ok((left, right))
+}
+
+/// Like [`parse`], but sorts the lists with [`sort::radix_sort`], as the IDs are only 5 digits.
+fn parse_radix(input: &str) -> Result<(Vec<u32>, Vec<u32>), ParseError> {
+    let (mut left, mut right) = read_lists(input)?;
+
+    sort::radix_sort(&mut left);
+    sort::radix_sort(&mut right);
+
+    Ok((left, right))
+}
+
+#[aoc_generator(day1, part1, radix)]
+fn parse_radix_part1(input: &str) -> Result<(Vec<u32>, Vec<u32>), ParseError> {
+    parse_radix(input)
+}
+
+#[aoc_generator(day1, part2, radix)]
+fn parse_radix_part2(input: &str) -> Result<(Vec<u32>, Vec<u32>), ParseError> {
+    parse_radix(input)
+}
+
+#[aoc(day1, part1, radix)]
+fn part1_radix(sides: &(Vec<u32>, Vec<u32>)) -> Result<u64, OverflowError> {
+    part1_impl(sides)
+}
+
+#[aoc(day1, part2, radix)]
+fn part2_radix(sides: &(Vec<u32>, Vec<u32>)) -> Result<u64, OverflowError> {
+    part2_impl(sides)
+}
+
+/// Where the numbers sit on each line, when every line is laid out the same, like `NNNNN   NNNNN`.
+#[derive(Debug, PartialEq)]
+struct Layout {
+    left: usize,
+    gap: usize,
+    right: usize,
+}
+
+impl Layout {
+    /// Takes the layout from the first line, if it's one [`number::parse_swar`] can decode.
+    fn detect(line: &[u8]) -> Option<Layout> {
+        let left = line.iter().position(|b| !b.is_ascii_digit())?;
+        let gap = line[left..].iter().position(|b| *b != b' ')?;
+        let right = line.len() - left - gap;
+
+        ((1..=8).contains(&left) && (1..=8).contains(&right)).then_some(Layout { left, gap, right })
+    }
+
+    fn width(&self) -> usize {
+        self.left + self.gap + self.right
+    }
+}
+
+/// Parses inputs where every line has the same layout by jumping straight to the numbers and
+/// decoding them with SWAR. Anything irregular goes through [`parse`] instead, which also takes
+/// care of reporting malformed input.
+fn parse_swar(input: &str) -> Result<(Vec<u32>, Vec<u32>), ParseError> {
+    let bytes = input.trim_ascii_end().as_bytes();
+    let first = bytes.split(|b| *b == b'\n').next().unwrap_or_default();
+    let Some(layout) = Layout::detect(first) else {
+        return parse(input);
+    };
+
+    // Every line but the last is followed by a newline.
+    let stride = layout.width() + 1;
+    if !(bytes.len() + 1).is_multiple_of(stride) {
+        return parse(input);
+    }
+
+    let mut left = Vec::with_capacity((bytes.len() + 1) / stride);
+    let mut right = Vec::with_capacity((bytes.len() + 1) / stride);
+
+    for line in bytes.chunks(stride) {
+        let (l, rest) = line.split_at(layout.left);
+        let (gap, rest) = rest.split_at(layout.gap);
+        let (r, end) = rest.split_at(layout.right);
+
+        match (number::parse_swar(l), number::parse_swar(r)) {
+            (Some(l), Some(r)) if gap.iter().all(|b| *b == b' ') && matches!(end, [] | [b'\n']) => {
+                left.push(l);
+                right.push(r);
+            }
+            _ => return parse(input),
+        }
+    }
 
     left.sort_unstable();
     right.sort_unstable();
 
-    (left, right)
+    Ok((left, right))
+}
+
+#[aoc_generator(day1, part1, swar)]
+fn parse_swar_part1(input: &str) -> Result<(Vec<u32>, Vec<u32>), ParseError> {
+    parse_swar(input)
+}
+
+#[aoc_generator(day1, part2, swar)]
+fn parse_swar_part2(input: &str) -> Result<(Vec<u32>, Vec<u32>), ParseError> {
+    parse_swar(input)
+}
+
+#[aoc(day1, part1, swar)]
+fn part1_swar(sides: &(Vec<u32>, Vec<u32>)) -> Result<u64, OverflowError> {
+    part1_impl(sides)
+}
+
+#[aoc(day1, part2, swar)]
+fn part2_swar(sides: &(Vec<u32>, Vec<u32>)) -> Result<u64, OverflowError> {
+    part2_impl(sides)
+}
+
+/// Parses a line of the input: a location ID from each list, separated by spaces.
+fn location_ids(input: &mut &str) -> PResult<(u32, u32)> {
+    separated_pair(
+        dec_uint.context(expect("a location ID")),
+        take_while(1.., ' ').context(expect("a second location ID")),
+        dec_uint.context(expect("a second location ID")),
+    )
+    .parse_next(input)
+}
+
+/// Parses the input with a grammar, which unlike [`parse`] also rejects trailing whitespace and
+/// blank lines between the pairs.
+fn parse_strict(input: &str) -> Result<(Vec<u32>, Vec<u32>), ParseError> {
+    let pairs = grammar::parse(1, input, grammar::lines(location_ids))?;
+    let (mut left, mut right): (Vec<_>, Vec<_>) = pairs.into_iter().unzip();
+
+    left.sort_unstable();
+    right.sort_unstable();
+
+    Ok((left, right))
+}
+
+#[aoc_generator(day1, part1, strict)]
+fn parse_strict_part1(input: &str) -> Result<(Vec<u32>, Vec<u32>), ParseError> {
+    parse_strict(input)
+}
+
+#[aoc_generator(day1, part2, strict)]
+fn parse_strict_part2(input: &str) -> Result<(Vec<u32>, Vec<u32>), ParseError> {
+    parse_strict(input)
+}
+
+#[aoc(day1, part1, strict)]
+fn part1_strict(sides: &(Vec<u32>, Vec<u32>)) -> Result<u64, OverflowError> {
+    part1_impl(sides)
 }
 
+#[aoc(day1, part2, strict)]
+fn part2_strict(sides: &(Vec<u32>, Vec<u32>)) -> Result<u64, OverflowError> {
+    part2_impl(sides)
+}
+
+const DISTANCE: Answer = Answer::new(1, "the total distance");
+const SIMILARITY: Answer = Answer::new(1, "the similarity score");
+
 #[aoc(day1, part1)]
-fn part1_impl(sides: &(Vec<u32>, Vec<u32>)) -> u32 {
+fn part1_impl(sides: &(Vec<u32>, Vec<u32>)) -> Result<u64, OverflowError> {
     let (left, right) = sides;
-    left.iter().zip(right).map(|(l, r)| l.abs_diff(*r)).sum()
+    distance(left, right)
+}
+
+/// Works out the total distance between two sorted lists.
+pub fn distance<T: LocationId>(left: &[T], right: &[T]) -> Result<u64, OverflowError> {
+    DISTANCE.sum(left.iter().zip(right).map(|(l, r)| l.distance(*r)))
 }
 
 #[aoc(day1, part2)]
-fn part2_impl(sides: &(Vec<u32>, Vec<u32>)) -> u32 {
+fn part2_impl(sides: &(Vec<u32>, Vec<u32>)) -> Result<u64, OverflowError> {
     let (left, right) = sides;
+    similarity_sorted(left, right)
+}
 
-    // We have sorted the lists as part of parsing. Chunk by the distinct values in each list and
-    // make it peekable because there aren't any side-effects here to be concerned about.
-    let mut left = left.chunk_by(|x, y| x == y).peekable();
-    let mut right = right.chunk_by(|x, y| x == y).peekable();
+/// Works out the similarity score of two sorted lists by walking them together.
+pub fn similarity_sorted<T: LocationId>(
+    left: &[T],
+    right: &[T],
+) -> Result<T::Score, OverflowError> {
+    merge_join(left, right, Join::Inner).try_fold(T::Score::default(), |sum, (id, n, m)| {
+        T::add_score(sum, id.score(n as u64, m as u64)?)
+    })
+}
 
-    let mut sum = 0;
+/// Works out the similarity score without sorting, by counting the right list in a hash map.
+pub fn similarity_hashed<T: LocationId>(
+    left: &[T],
+    right: &[T],
+) -> Result<T::Score, OverflowError> {
+    let mut counts = HashMap::default();
+    for id in right {
+        *counts.entry(*id).or_insert(0) += 1;
+    }
 
-    loop {
-        let Some(&x) = left.peek() else {
-            break;
-        };
-        let Some(&y) = right.peek() else {
-            break;
-        };
-        match x[0].cmp(&y[0]) {
-            std::cmp::Ordering::Less => {
-                left.next();
-            }
-            std::cmp::Ordering::Equal => {
-                sum += x[0] * x.len() as u32 * y.len() as u32;
-                left.next();
-                right.next();
-            }
-            std::cmp::Ordering::Greater => {
-                right.next();
-            }
+    left.iter().try_fold(T::Score::default(), |sum, id| {
+        T::add_score(sum, id.score(1, counts.get(id).copied().unwrap_or(0))?)
+    })
+}
+
+/// The largest ID we'll ever count in an array, rather than a hash map. Real IDs are 5 digits.
+const DENSE_LIMIT: u32 = 1 << 24;
+
+/// How many entries per ID in the right list the array of counts can have, once it's bigger than
+/// [`DENSE_SMALL`], so a short list with a big ID in it doesn't need a big array.
+const DENSE_PER_ID: usize = 64;
+
+/// Arrays of counts this long are cheap whatever's in them, and fit every 5-digit ID.
+const DENSE_SMALL: usize = 1 << 17;
+
+/// Whether to count `len` IDs up to `max` in an array.
+fn counts_densely(max: u32, len: usize) -> bool {
+    max < DENSE_LIMIT && (max as usize) < DENSE_SMALL.max(DENSE_PER_ID * len)
+}
+
+/// Works out the similarity score without sorting, by counting the right list in an array
+/// indexed by ID. Falls back to [`similarity_hashed`] when the IDs are too big for the length of
+/// the list.
+pub fn similarity_dense(left: &[u32], right: &[u32]) -> Result<u64, OverflowError> {
+    let Some(&max) = right.iter().max() else {
+        return Ok(0);
+    };
+    if !counts_densely(max, right.len()) {
+        return similarity_hashed(left, right);
+    }
+
+    let mut counts = vec![0_u32; max as usize + 1];
+    for id in right {
+        counts[*id as usize] += 1;
+    }
+
+    left.iter().try_fold(0, |sum, id| {
+        let count = counts.get(*id as usize).copied().unwrap_or(0);
+        let score = id.score(1, count as u64)?;
+        SIMILARITY.add(sum, score)
+    })
+}
+
+/// Solves part 1 for lists that weren't sorted when they were parsed.
+fn part1_unsorted(sides: &(Vec<u32>, Vec<u32>)) -> Result<u64, OverflowError> {
+    let (mut left, mut right) = sides.clone();
+    left.sort_unstable();
+    right.sort_unstable();
+    part1_impl(&(left, right))
+}
+
+#[aoc_generator(day1, part2, hashmap)]
+fn parse_hashmap(input: &str) -> Result<(Vec<u32>, Vec<u32>), ParseError> {
+    read_lists(input)
+}
+
+#[aoc(day1, part2, hashmap)]
+fn part2_hashmap(sides: &(Vec<u32>, Vec<u32>)) -> Result<u64, OverflowError> {
+    similarity_hashed(&sides.0, &sides.1)
+}
+
+#[aoc_generator(day1, part2, dense)]
+fn parse_dense(input: &str) -> Result<(Vec<u32>, Vec<u32>), ParseError> {
+    read_lists(input)
+}
+
+#[aoc(day1, part2, dense)]
+fn part2_dense(sides: &(Vec<u32>, Vec<u32>)) -> Result<u64, OverflowError> {
+    similarity_dense(&sides.0, &sides.1)
+}
+
+/// Both lists, sorted, with the IDs read as the narrowest integer type that fits them all.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Lists {
+    U32(Vec<u32>, Vec<u32>),
+    U64(Vec<u64>, Vec<u64>),
+    I32(Vec<i32>, Vec<i32>),
+    I64(Vec<i64>, Vec<i64>),
+}
+
+impl Lists {
+    /// Reads both lists, picking the type of the IDs from the input: `u32`s like the puzzle's
+    /// IDs if they fit, or else `u64`s, unless there are signs, when they're `i32`s or `i64`s.
+    /// The input is only read once, as the wider type, and then narrowed if every ID fits.
+    pub fn parse(input: &str) -> Result<Self, ParseError> {
+        if input.contains(['-', '+']) {
+            let (left, right) = parse_lists::<i64>(input)?;
+            Ok(match (narrow(&left), narrow(&right)) {
+                (Some(left), Some(right)) => Lists::I32(left, right),
+                _ => Lists::I64(left, right),
+            })
+        } else {
+            let (left, right) = parse_lists::<u64>(input)?;
+            Ok(match (narrow(&left), narrow(&right)) {
+                (Some(left), Some(right)) => Lists::U32(left, right),
+                _ => Lists::U64(left, right),
+            })
+        }
+    }
+
+    /// Works out the total distance between the lists, as in part 1.
+    pub fn distance(&self) -> Result<u64, OverflowError> {
+        match self {
+            Lists::U32(left, right) => distance(left, right),
+            Lists::U64(left, right) => distance(left, right),
+            Lists::I32(left, right) => distance(left, right),
+            Lists::I64(left, right) => distance(left, right),
         }
     }
-    sum
+
+    /// Works out the similarity score of the lists, as in part 2, which is only ever negative
+    /// when some IDs are.
+    pub fn similarity(&self) -> Result<i128, OverflowError> {
+        match self {
+            Lists::U32(left, right) => similarity_sorted(left, right).map(Into::into),
+            Lists::U64(left, right) => similarity_sorted(left, right).map(Into::into),
+            Lists::I32(left, right) => similarity_sorted(left, right),
+            Lists::I64(left, right) => similarity_sorted(left, right),
+        }
+    }
+}
+
+/// Converts the IDs to a narrower type, if they all fit. Sorted IDs stay sorted.
+fn narrow<W: Copy, T: TryFrom<W>>(ids: &[W]) -> Option<Vec<T>> {
+    ids.iter().map(|id| T::try_from(*id).ok()).collect()
+}
+
+#[aoc_generator(day1, part1, any)]
+fn parse_any_part1(input: &str) -> Result<Lists, ParseError> {
+    Lists::parse(input)
+}
+
+#[aoc_generator(day1, part2, any)]
+fn parse_any_part2(input: &str) -> Result<Lists, ParseError> {
+    Lists::parse(input)
+}
+
+#[aoc(day1, part1, any)]
+fn part1_any(lists: &Lists) -> Result<u64, OverflowError> {
+    lists.distance()
+}
+
+#[aoc(day1, part2, any)]
+fn part2_any(lists: &Lists) -> Result<i128, OverflowError> {
+    lists.similarity()
 }
 
 // For CodSpeed - see https://codspeed.io/advent
-pub fn part1(input: &str) -> u32 {
-    part1_impl(&parse(input))
+pub fn part1(input: &str) -> Result<u64, SolveError> {
+    Ok(part1_impl(&parse(input)?)?)
 }
 
 // For CodSpeed - see https://codspeed.io/advent
-pub fn part2(input: &str) -> u32 {
-    part2_impl(&parse(input))
+pub fn part2(input: &str) -> Result<u64, SolveError> {
+    Ok(part2_impl(&parse(input)?)?)
+}
+
+/// A location ID from each list, paired up by part 1, and how far apart they are.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Pair {
+    pub left: u32,
+    pub right: u32,
+    pub distance: u32,
+}
+
+/// A location ID in both lists, with how many times it's in each and what it adds to part 2's
+/// similarity score.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Match {
+    pub id: u32,
+    pub left: usize,
+    pub right: usize,
+    pub score: u64,
+}
+
+/// How both answers are made up, so a surprising one can be traced back to the input.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Explanation {
+    /// Part 1's answer.
+    pub distance: u64,
+    /// Every pair, smallest IDs first.
+    pub pairs: Vec<Pair>,
+    /// Part 2's answer.
+    pub similarity: u64,
+    /// Every ID in both lists, smallest first.
+    pub matches: Vec<Match>,
+}
+
+impl Explanation {
+    /// Returns the `k` pairs furthest apart, which add the most to part 1, furthest first.
+    pub fn top_pairs(&self, k: usize) -> Vec<Pair> {
+        let mut pairs = self.pairs.clone();
+        pairs.sort_by_key(|p| std::cmp::Reverse(p.distance));
+        pairs.truncate(k);
+        pairs
+    }
+
+    /// Returns the `k` matches that add the most to part 2, biggest first.
+    pub fn top_matches(&self, k: usize) -> Vec<Match> {
+        let mut matches = self.matches.clone();
+        matches.sort_by_key(|m| std::cmp::Reverse(m.score));
+        matches.truncate(k);
+        matches
+    }
+}
+
+/// Works out both answers the same way as [`part1`] and [`part2`], keeping every pair and match
+/// that goes into them.
+pub fn explain(input: &str) -> Result<Explanation, SolveError> {
+    let (left, right) = parse(input)?;
+
+    let pairs = left
+        .iter()
+        .zip(&right)
+        .map(|(l, r)| Pair {
+            left: *l,
+            right: *r,
+            distance: l.abs_diff(*r),
+        })
+        .collect::<Vec<_>>();
+
+    let matches = merge_join(&left, &right, Join::Inner)
+        .map(|(id, n, m)| {
+            Ok(Match {
+                id: *id,
+                left: n,
+                right: m,
+                score: id.score(n as u64, m as u64)?,
+            })
+        })
+        .collect::<Result<Vec<_>, OverflowError>>()?;
+
+    Ok(Explanation {
+        distance: DISTANCE.sum(pairs.iter().map(|p| p.distance as u64))?,
+        pairs,
+        similarity: SIMILARITY.sum(matches.iter().map(|m| m.score))?,
+        matches,
+    })
+}
+
+/// Checks that every line has a location ID for each list, so the two lists come out the same
+/// length, returning every problem rather than stopping at the first like [`parse`].
+pub fn validate(input: &str) -> Vec<ParseError> {
+    let mut errors = Vec::new();
+
+    for (n, line) in input::lines(input).enumerate() {
+        let mut fields = line.split_ascii_whitespace();
+        for expected in ["a location ID", "a second location ID"] {
+            match fields.next() {
+                Some(field) => errors.extend(
+                    parse_field(1, n + 1, column_of(line, field), field, expected).err(),
+                ),
+                None => {
+                    errors.push(ParseError::new(1, n + 1, line.len() + 1, "", expected));
+                    break;
+                }
+            }
+        }
+        if let Some(extra) = fields.next() {
+            let column = column_of(line, extra);
+            errors.push(ParseError::new(1, n + 1, column, extra, "the end of the line"));
+        }
+    }
+
+    errors
 }
 
 #[cfg(test)]
 mod tests {
+    use proptest::prelude::*;
+
     use super::*;
 
     #[test]
@@ -87,8 +770,9 @@ mod tests {
 1   3
 3   9
 3   3"
-            )),
-            11
+            )
+            .unwrap()),
+            Ok(11)
         );
     }
 
@@ -102,13 +786,241 @@ mod tests {
 1   3
 3   9
 3   3"
-            )),
-            31
+            )
+            .unwrap()),
+            Ok(31)
         );
     }
 
     #[test]
     fn hand_parsing() {
-        assert_eq!(91527, parse_number("91527"))
+        assert_eq!(91527, crate::number::parse::<u32>(b"91527"));
+        assert_eq!(parse("123456   4"), Ok((vec![123456], vec![4])));
+    }
+
+    #[test]
+    fn handles_line_endings() {
+        let example = "3   4\n4   3\n2   5\n1   3\n3   9\n3   3";
+        for input in crate::input::variants(example) {
+            assert_eq!(part1(&input), Ok(11), "{input:?}");
+            assert_eq!(part2(&input), Ok(31), "{input:?}");
+            assert_eq!(parse_swar(&input), parse(example), "{input:?}");
+        }
+    }
+
+    #[test]
+    fn detects_layout() {
+        assert_eq!(
+            Layout::detect(b"91527   12345"),
+            Some(Layout {
+                left: 5,
+                gap: 3,
+                right: 5
+            })
+        );
+        assert_eq!(Layout::detect(b"91527"), None);
+        assert_eq!(Layout::detect(b"91527   "), None);
+        assert_eq!(Layout::detect(b"123456789 1"), None);
+    }
+
+    #[test]
+    fn swar_matches_scalar() {
+        for input in [
+            "91527   12345\n00001   99999\n31415   27182",
+            // Irregular lines fall back to the scalar parser.
+            "3   4\n10   20",
+            "3   4\n1   2 ",
+            "3   4\n1\t  2",
+            "123456789   1",
+            // As do malformed ones, so errors are reported the same way.
+            "3   4\n4   x",
+            "3   4\n4",
+            "",
+        ] {
+            assert_eq!(parse_swar(input), parse(input), "{input:?}");
+        }
+    }
+
+    #[test]
+    fn strict_parser_checks_everything() {
+        let example = "3   4\n4   3\n2   5\n1   3\n3   9\n3   3";
+        assert_eq!(parse_strict(example), parse(example));
+        assert_eq!(parse_strict("3   4\r\n4   3\n\n"), parse("3   4\n4   3"));
+
+        for (input, err) in [
+            ("3   4\n4", ParseError::new(1, 2, 2, "", "a second location ID")),
+            ("3   4\n4   x3", ParseError::new(1, 2, 5, "x3", "a second location ID")),
+            ("4294967296   4", ParseError::new(1, 1, 1, "4294967296", "a location ID")),
+            ("3   4   5", ParseError::new(1, 1, 6, "", "the end of the line")),
+            ("3   4 \n1   2", ParseError::new(1, 1, 6, "", "the end of the line")),
+            ("3   4\n\n1   2", ParseError::new(1, 2, 1, "", "a location ID")),
+            ("3\t4", ParseError::new(1, 1, 2, "", "a second location ID")),
+        ] {
+            assert_eq!(parse_strict(input), Err(err), "{input:?}");
+        }
+    }
+
+    #[test]
+    fn counts_without_sorting() {
+        let left = [3, 4, 2, 1, 3, 3];
+        let right = [4, 3, 5, 3, 9, 3];
+        assert_eq!(similarity_hashed(&left, &right), Ok(31));
+        assert_eq!(similarity_dense(&left, &right), Ok(31));
+        assert_eq!(similarity_dense(&left, &[]), Ok(0));
+        assert_eq!(
+            similarity_dense(&[DENSE_LIMIT], &[DENSE_LIMIT, 1]),
+            Ok(DENSE_LIMIT as u64)
+        );
+
+        // A short list with a big ID in it is hashed, rather than counted in a big array.
+        let big = DENSE_SMALL as u32 * 2;
+        assert!(!counts_densely(big, 3));
+        assert_eq!(similarity_dense(&[big, 7], &[big, 7, 7]), Ok(big as u64 + 14));
+        assert!(counts_densely(99_999, 3));
+        assert!(counts_densely(big, 10_000));
+        assert!(!counts_densely(DENSE_LIMIT, 1 << 20));
+
+        let input = "3   4\n4   3\n2   5\n1   3\n3   9\n3   3";
+        for variant in ["hashmap", "dense"] {
+            let parsed = crate::find(1, Some(variant)).unwrap().parse(input).unwrap();
+            assert_eq!(parsed.part1(), Ok("11".to_string()), "{variant}");
+            assert_eq!(parsed.part2(), Ok("31".to_string()), "{variant}");
+        }
+    }
+
+    proptest! {
+        #[test]
+        fn counting_matches_merging(
+            mut left in prop::collection::vec(0..1000_u32, 0..500),
+            mut right in prop::collection::vec(0..1000_u32, 0..500),
+        ) {
+            let hashed = similarity_hashed(&left, &right);
+            let dense = similarity_dense(&left, &right);
+
+            left.sort_unstable();
+            right.sort_unstable();
+            let merged = similarity_sorted(&left, &right);
+
+            prop_assert_eq!(hashed, merged);
+            prop_assert_eq!(dense, merged);
+        }
+
+        #[test]
+        fn radix_parser_matches_parse(
+            pairs in prop::collection::vec((0..100_000_u32, 0..100_000_u32), 0..1500),
+        ) {
+            let input = pairs
+                .iter()
+                .map(|(l, r)| format!("{l:05}   {r:05}"))
+                .collect::<Vec<_>>()
+                .join("\n");
+            prop_assert_eq!(parse_radix(&input), parse(&input));
+        }
+    }
+
+    #[test]
+    fn explains_both_answers() {
+        let explanation = explain("3   4\n4   3\n2   5\n1   3\n3   9\n3   3").unwrap();
+        assert_eq!(explanation.distance, 11);
+        assert_eq!(explanation.similarity, 31);
+
+        let pair = |left, right, distance| Pair {
+            left,
+            right,
+            distance,
+        };
+        assert_eq!(
+            explanation.pairs,
+            [
+                pair(1, 3, 2),
+                pair(2, 3, 1),
+                pair(3, 3, 0),
+                pair(3, 4, 1),
+                pair(3, 5, 2),
+                pair(4, 9, 5)
+            ]
+        );
+        assert_eq!(explanation.top_pairs(2), [pair(4, 9, 5), pair(1, 3, 2)]);
+
+        let m = |id, left, right, score| Match {
+            id,
+            left,
+            right,
+            score,
+        };
+        assert_eq!(explanation.matches, [m(3, 3, 3, 27), m(4, 1, 1, 4)]);
+        assert_eq!(explanation.top_matches(5), explanation.matches);
+        assert_eq!(explanation.top_matches(0), []);
+
+        assert!(explain("3   4\n4").is_err());
+    }
+
+    #[test]
+    fn reads_whatever_type_fits() {
+        let lists = Lists::parse("3   4\n4   3\n2   5\n1   3\n3   9\n3   3").unwrap();
+        assert!(matches!(lists, Lists::U32(..)));
+        assert_eq!(lists.distance(), Ok(11));
+        assert_eq!(lists.similarity(), Ok(31));
+
+        let lists = Lists::parse("-3   4\n4   -3\n-2   -3").unwrap();
+        assert_eq!(lists, Lists::I32(vec![-3, -2, 4], vec![-3, -3, 4]));
+        // Both lists have the same type, so one big ID widens the other list too.
+        assert_eq!(
+            Lists::parse("5000000000   2\n1   1"),
+            Ok(Lists::U64(vec![1, 5_000_000_000], vec![1, 2]))
+        );
+        assert_eq!(lists.distance(), Ok(1));
+        assert_eq!(lists.similarity(), Ok(-2));
+
+        let lists = Lists::parse("5000000000   1\n1   5000000000").unwrap();
+        assert!(matches!(lists, Lists::U64(..)));
+        assert_eq!(lists.distance(), Ok(0));
+        assert_eq!(lists.similarity(), Ok(5_000_000_001));
+
+        let lists = Lists::parse("-5000000000   +1\n1   -5000000000").unwrap();
+        assert!(matches!(lists, Lists::I64(..)));
+        assert_eq!(lists.similarity(), Ok(-4_999_999_999));
+
+        assert_eq!(
+            Lists::parse("-1   x"),
+            Err(ParseError::new(1, 1, 6, "x", "a second location ID"))
+        );
+        assert!(Lists::parse("18446744073709551616   1").is_err());
+        assert!(Lists::parse("9223372036854775808   -1").is_err());
+    }
+
+    #[test]
+    fn validates_every_line() {
+        assert_eq!(validate("3   4\r\n4   3\n"), []);
+        assert_eq!(
+            validate("3   4\n4\nx   y\n\n1   2   3"),
+            [
+                ParseError::new(1, 2, 2, "", "a second location ID"),
+                ParseError::new(1, 3, 1, "x", "a location ID"),
+                ParseError::new(1, 3, 5, "y", "a second location ID"),
+                ParseError::new(1, 4, 1, "", "a location ID"),
+                ParseError::new(1, 5, 9, "3", "the end of the line"),
+            ]
+        );
+    }
+
+    #[test]
+    fn reports_malformed_lines() {
+        assert_eq!(
+            parse("3   4\n4"),
+            Err(ParseError::new(1, 2, 2, "", "a second location ID"))
+        );
+        assert_eq!(
+            parse("3   4\n4   x3"),
+            Err(ParseError::new(1, 2, 5, "x3", "a second location ID"))
+        );
+        assert_eq!(
+            parse("4294967296   4"),
+            Err(ParseError::new(1, 1, 1, "4294967296", "a location ID"))
+        );
+        assert_eq!(
+            parse("3   4   5"),
+            Err(ParseError::new(1, 1, 9, "5", "the end of the line"))
+        );
     }
 }
@@ -0,0 +1,374 @@
+//! An index of location IDs that come and go, keeping day 1's answers up to date as they do rather
+//! than sorting both lists again.
+//!
+//! The total distance between the sorted lists is the sum, over every ID `x`, of how many more IDs
+//! up to `x` there are in the left list than the right, ignoring the sign. Adding or removing an
+//! ID changes that difference by one for it and every ID above it, so the differences are kept in
+//! blocks that can each be shifted in one go. Each block tallies its differences to know how many
+//! move towards zero and how many away. With room for `n` IDs, the blocks are about `√n` long, so
+//! an update takes `O(√n)` steps. The similarity score only changes by the score of the ID added
+//! or removed.
+
+use rustc_hash::FxHashMap as HashMap;
+
+use crate::IdTooBigError;
+
+/// The largest ID the index takes. IDs are indexed directly, so this keeps its memory to a few
+/// dozen MiB. Real IDs are 5 digits.
+pub const MAX_ID: u32 = (1 << 20) - 1;
+
+/// One of the two lists of location IDs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum List {
+    Left,
+    Right,
+}
+
+/// Both lists of location IDs, with the total distance and similarity score kept up to date as
+/// IDs are inserted and removed.
+///
+/// IDs are indexed directly, so memory grows with the largest ID seen rather than with how many
+/// IDs there are, up to [`MAX_ID`]. Totals too big for a `u64` wrap, but come right again once
+/// they fit.
+#[derive(Debug, Clone, Default)]
+pub struct LiveIndex {
+    /// How many times each ID is in the left and the right list, with room for every ID up to a
+    /// power of two.
+    counts: [Vec<u32>; 2],
+    lens: [usize; 2],
+    /// How many IDs' differences each of `blocks` holds.
+    block: usize,
+    /// How many more IDs up to each ID there are in the left list than the right.
+    blocks: Vec<Block>,
+    /// The sum of the differences in `blocks`, ignoring the sign.
+    distance: u64,
+    similarity: u64,
+}
+
+impl LiveIndex {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Indexes two whole lists at once, which is quicker than inserting their IDs one by one.
+    pub fn from_lists(left: &[u32], right: &[u32]) -> Result<Self, IdTooBigError> {
+        let mut index = Self::new();
+        let Some(&max) = left.iter().chain(right).max() else {
+            return Ok(index);
+        };
+        check(max)?;
+
+        let room = (max as usize + 1).next_power_of_two();
+        for (counts, list) in index.counts.iter_mut().zip([left, right]) {
+            counts.resize(room, 0);
+            for id in list {
+                counts[*id as usize] += 1;
+            }
+        }
+        index.lens = [left.len(), right.len()];
+        index.rebuild();
+
+        Ok(index)
+    }
+
+    /// Adds `id` to one of the lists, unless it's bigger than [`MAX_ID`].
+    pub fn insert(&mut self, list: List, id: u32) -> Result<(), IdTooBigError> {
+        check(id)?;
+        self.grow(id);
+        self.change(list, id, 1);
+        Ok(())
+    }
+
+    /// Removes one of `id` from one of the lists, returning whether it was there.
+    pub fn remove(&mut self, list: List, id: u32) -> bool {
+        if self.count(list, id) == 0 {
+            return false;
+        }
+        self.change(list, id, -1);
+        true
+    }
+
+    /// How many times `id` is in one of the lists.
+    pub fn count(&self, list: List, id: u32) -> u32 {
+        self.counts[list as usize]
+            .get(id as usize)
+            .copied()
+            .unwrap_or(0)
+    }
+
+    /// How many IDs are in one of the lists.
+    pub fn len(&self, list: List) -> usize {
+        self.lens[list as usize]
+    }
+
+    /// The total distance between the lists, as in part 1. The IDs only pair up when both lists
+    /// are the same length, so until then there isn't one.
+    pub fn distance(&self) -> Option<u64> {
+        (self.lens[0] == self.lens[1]).then_some(self.distance)
+    }
+
+    /// The similarity score of the lists, as in part 2.
+    pub fn similarity(&self) -> u64 {
+        self.similarity
+    }
+
+    /// Adds `by`, which is 1 or -1, to how many times `id` is in `list`, which must already have
+    /// room for it.
+    fn change(&mut self, list: List, id: u32, by: i64) {
+        let (this, other) = (list as usize, 1 - list as usize);
+        let score = id as u64 * self.counts[other][id as usize] as u64;
+        if by > 0 {
+            self.counts[this][id as usize] += 1;
+            self.lens[this] += 1;
+            self.similarity = self.similarity.wrapping_add(score);
+        } else {
+            self.counts[this][id as usize] -= 1;
+            self.lens[this] -= 1;
+            self.similarity = self.similarity.wrapping_sub(score);
+        }
+
+        // IDs in the right list count against the difference.
+        let by = if list == List::Left { by } else { -by };
+        let (first, from) = (id as usize / self.block, id as usize % self.block);
+        let mut change = self.blocks[first].add(from, by);
+        for block in &mut self.blocks[first + 1..] {
+            change += block.add(0, by);
+        }
+        self.distance = self.distance.wrapping_add_signed(change);
+    }
+
+    /// Makes room for IDs up to `id`, at least doubling the room each time so that rebuilding the
+    /// blocks only takes `O(1)` steps per insert on average.
+    fn grow(&mut self, id: u32) {
+        let room = id as usize + 1;
+        if room <= self.counts[0].len() {
+            return;
+        }
+
+        for counts in &mut self.counts {
+            counts.resize(room.next_power_of_two(), 0);
+        }
+        self.rebuild();
+    }
+
+    /// Works out the blocks and both answers from the counts, with blocks about the square root
+    /// of the room for IDs long.
+    fn rebuild(&mut self) {
+        // The room is a power of two, so this divides it.
+        self.block = 1 << self.counts[0].len().trailing_zeros().div_ceil(2);
+        self.distance = 0;
+        self.similarity = 0;
+
+        let [left, right] = &self.counts;
+        let block = self.block;
+        let mut diff = 0;
+        self.blocks = (0..left.len() / block)
+            .map(|i| {
+                let diffs = (i * block..(i + 1) * block)
+                    .map(|id| {
+                        diff += left[id] as i64 - right[id] as i64;
+                        self.distance = self.distance.wrapping_add(diff.unsigned_abs());
+                        self.similarity = self
+                            .similarity
+                            .wrapping_add(id as u64 * left[id] as u64 * right[id] as u64);
+                        diff
+                    })
+                    .collect();
+                Block::new(diffs)
+            })
+            .collect();
+    }
+}
+
+/// Fails if `id` is too big for the index.
+fn check(id: u32) -> Result<(), IdTooBigError> {
+    if id > MAX_ID {
+        return Err(IdTooBigError { id, max: MAX_ID });
+    }
+    Ok(())
+}
+
+/// The differences for a run of IDs, which can all be shifted at once.
+#[derive(Debug, Clone)]
+struct Block {
+    /// Added to every difference in `diffs`.
+    shift: i64,
+    diffs: Vec<i64>,
+    /// How many IDs have each difference in `diffs`.
+    tally: HashMap<i64, u32>,
+    /// How many IDs have a difference below zero, once shifted.
+    negative: u32,
+}
+
+impl Block {
+    fn new(diffs: Vec<i64>) -> Self {
+        let mut tally = HashMap::default();
+        for diff in &diffs {
+            *tally.entry(*diff).or_default() += 1;
+        }
+        let negative = diffs.iter().filter(|diff| **diff < 0).count() as u32;
+
+        Block {
+            shift: 0,
+            diffs,
+            tally,
+            negative,
+        }
+    }
+
+    /// How many IDs have a difference of `diff`, once shifted.
+    fn tally(&self, diff: i64) -> u32 {
+        self.tally.get(&(diff - self.shift)).copied().unwrap_or(0)
+    }
+
+    /// Adds `by`, which is 1 or -1, to the difference of every ID in the block from the `from`th
+    /// on, returning how much that changes their sum ignoring the sign.
+    fn add(&mut self, from: usize, by: i64) -> i64 {
+        let len = self.diffs.len();
+        if from > 0 {
+            return (from..len).map(|i| self.add_one(i, by)).sum();
+        }
+
+        // Differences on one side of zero get closer to it, and the rest further away.
+        let change = if by > 0 {
+            let change = len as i64 - 2 * self.negative as i64;
+            self.negative -= self.tally(-1);
+            change
+        } else {
+            let zeros = self.tally(0);
+            let positive = len as u32 - self.negative - zeros;
+            self.negative += zeros;
+            len as i64 - 2 * positive as i64
+        };
+        self.shift += by;
+        change
+    }
+
+    fn add_one(&mut self, i: usize, by: i64) -> i64 {
+        let old = self.diffs[i];
+        if let Some(n) = self.tally.get_mut(&old) {
+            *n -= 1;
+            if *n == 0 {
+                self.tally.remove(&old);
+            }
+        }
+        *self.tally.entry(old + by).or_default() += 1;
+        self.diffs[i] = old + by;
+
+        let (before, after) = (old + self.shift, old + self.shift + by);
+        if before < 0 {
+            self.negative -= 1;
+        }
+        if after < 0 {
+            self.negative += 1;
+        }
+        after.abs() - before.abs()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use proptest::prelude::*;
+
+    use super::*;
+    use crate::day1::similarity_hashed;
+
+    const LEFT: [u32; 6] = [3, 4, 2, 1, 3, 3];
+    const RIGHT: [u32; 6] = [4, 3, 5, 3, 9, 3];
+
+    /// Works out the total distance from scratch, by sorting the lists.
+    fn distance(left: &[u32], right: &[u32]) -> u64 {
+        let (mut left, mut right) = (left.to_vec(), right.to_vec());
+        left.sort_unstable();
+        right.sort_unstable();
+        left.iter()
+            .zip(&right)
+            .map(|(l, r)| l.abs_diff(*r) as u64)
+            .sum()
+    }
+
+    #[test]
+    fn keeps_the_example_answers() {
+        let mut index = LiveIndex::new();
+        for (l, r) in LEFT.iter().zip(&RIGHT) {
+            index.insert(List::Left, *l).unwrap();
+            assert_eq!(index.distance(), None);
+            index.insert(List::Right, *r).unwrap();
+        }
+        assert_eq!(index.distance(), Some(11));
+        assert_eq!(index.similarity(), 31);
+        assert_eq!(index.count(List::Right, 3), 3);
+
+        // Taking the 4 out of each list pairs up 1 2 3 3 3 with 3 3 3 5 9.
+        assert!(index.remove(List::Left, 4));
+        assert!(index.remove(List::Right, 4));
+        assert!(!index.remove(List::Right, 4));
+        assert!(!index.remove(List::Left, 1 << 20));
+        assert_eq!(index.distance(), Some(11));
+        assert_eq!(index.similarity(), 27);
+        assert_eq!(index.len(List::Left), 5);
+    }
+
+    #[test]
+    fn builds_from_lists() {
+        let index = LiveIndex::from_lists(&LEFT, &RIGHT).unwrap();
+        assert_eq!(index.distance(), Some(11));
+        assert_eq!(index.similarity(), 31);
+
+        let index = LiveIndex::from_lists(&[], &[]).unwrap();
+        assert_eq!(index.distance(), Some(0));
+        assert_eq!(index.similarity(), 0);
+    }
+
+    #[test]
+    fn refuses_ids_too_big() {
+        let mut index = LiveIndex::from_lists(&LEFT, &RIGHT).unwrap();
+        let err = IdTooBigError {
+            id: u32::MAX,
+            max: MAX_ID,
+        };
+        assert_eq!(index.insert(List::Left, u32::MAX), Err(err));
+        assert_eq!(LiveIndex::from_lists(&[1], &[u32::MAX]).err(), Some(err));
+        assert_eq!(index.len(List::Left), 6);
+
+        // The blocks grow with the room for IDs, staying about its square root long.
+        index.insert(List::Left, MAX_ID).unwrap();
+        index.insert(List::Right, 0).unwrap();
+        assert_eq!((index.counts[0].len(), index.block), (1 << 20, 1 << 10));
+        // 1 2 3 3 3 4 pair up with 0 3 3 3 4 5 for a distance of 4, leaving the big ID and the 9.
+        assert_eq!(index.distance(), Some(4 + MAX_ID as u64 - 9));
+    }
+
+    proptest! {
+        #[test]
+        fn matches_sorting_from_scratch(
+            start in prop::collection::vec((0..3000_u32, 0..3000_u32), 0..50),
+            ops in prop::collection::vec((any::<bool>(), any::<bool>(), 0..3000_u32), 0..200),
+        ) {
+            let (mut left, mut right): (Vec<u32>, Vec<u32>) = start.into_iter().unzip();
+            let mut index = LiveIndex::from_lists(&left, &right).unwrap();
+
+            for (insert, is_left, id) in ops {
+                let (list, ids) = if is_left {
+                    (List::Left, &mut left)
+                } else {
+                    (List::Right, &mut right)
+                };
+                if insert {
+                    index.insert(list, id).unwrap();
+                    ids.push(id);
+                } else {
+                    let at = ids.iter().position(|i| *i == id);
+                    prop_assert_eq!(index.remove(list, id), at.is_some());
+                    if let Some(at) = at {
+                        ids.swap_remove(at);
+                    }
+                }
+
+                let expected = (left.len() == right.len()).then(|| distance(&left, &right));
+                prop_assert_eq!(index.distance(), expected);
+                prop_assert_eq!(Ok(index.similarity()), similarity_hashed(&left, &right));
+            }
+        }
+    }
+}
@@ -1,87 +1,296 @@
+use std::{cmp::Ordering, io::BufRead};
+
 use aoc_runner_derive::{aoc, aoc_generator};
+use winnow::{
+    ascii::dec_uint,
+    combinator::{cut_err, separated},
+    PResult, Parser,
+};
+
+use crate::{
+    column_of,
+    grammar::{self, expect},
+    input, ParseError, ReadError, Solution, SolveError,
+};
+
+/// Works straight off the raw bytes, reusing a single buffer for each report.
+pub struct Day2;
+
+impl Solution for Day2 {
+    const DAY: u8 = 2;
+    const VARIANT: &'static str = "inline";
+
+    type Input<'a> = &'a str;
+    type Output1 = u64;
+    type Output2 = u64;
+
+    fn parse(input: &str) -> Result<Self::Input<'_>, ParseError> {
+        Ok(input)
+    }
+
+    fn part1(input: &Self::Input<'_>) -> Result<u64, SolveError> {
+        Ok(part1_inline(input, SafetyPolicy::PUZZLE)?)
+    }
+
+    fn part2(input: &Self::Input<'_>) -> Result<u64, SolveError> {
+        Ok(part2_inline(input, SafetyPolicy::PUZZLE, 1)?)
+    }
+}
+
+/// Parses every report up front, then clones reports to try removing levels.
+pub struct Day2Naive;
+
+impl Solution for Day2Naive {
+    const DAY: u8 = 2;
+    const VARIANT: &'static str = "naive";
+
+    type Input<'a> = Vec<Vec<u16>>;
+    type Output1 = u64;
+    type Output2 = u64;
+
+    fn parse(input: &str) -> Result<Self::Input<'_>, ParseError> {
+        parse(input)
+    }
+
+    fn part1(input: &Self::Input<'_>) -> Result<u64, SolveError> {
+        Ok(part1_naive(input))
+    }
+
+    fn part2(input: &Self::Input<'_>) -> Result<u64, SolveError> {
+        Ok(part2_naive(input))
+    }
+}
+
+/// Checks the input against a grammar, rejecting anything out of place.
+pub struct Day2Strict;
+
+impl Solution for Day2Strict {
+    const DAY: u8 = 2;
+    const VARIANT: &'static str = "strict";
+
+    type Input<'a> = Vec<Vec<u16>>;
+    type Output1 = u64;
+    type Output2 = u64;
+
+    fn parse(input: &str) -> Result<Self::Input<'_>, ParseError> {
+        parse_strict(input)
+    }
+
+    fn part1(input: &Self::Input<'_>) -> Result<u64, SolveError> {
+        Ok(part1_strict(input))
+    }
+
+    fn part2(input: &Self::Input<'_>) -> Result<u64, SolveError> {
+        Ok(part2_strict(input))
+    }
+}
 
 #[aoc_generator(day2)]
-fn parse(input: &str) -> Vec<Vec<u16>> {
+fn parse(input: &str) -> Result<Vec<Vec<u16>>, ParseError> {
     let mut res = Vec::with_capacity(1000);
 
-    input.as_bytes().split(|b| *b == b'\n').for_each(|l| {
+    for (n, l) in input::lines(input).enumerate() {
+        if l.is_empty() {
+            continue;
+        }
         let mut levels = Vec::with_capacity(8);
+        read_report(n + 1, l.as_bytes(), &mut levels)?;
+        res.push(levels);
+    }
 
-        for level in l
-            .split(|b| *b == b' ')
-            // skip empty lines like the last one in the input file...
-            .filter(|v| !v.is_empty())
-            .map(parse_u16)
-        {
-            levels.push(level);
-        }
+    Ok(res)
+}
 
-        res.push(levels);
-    });
+/// Reads the levels on line number `n` of the input into `report`, replacing what was there.
+fn read_report(n: usize, line: &[u8], report: &mut Vec<u16>) -> Result<(), ParseError> {
+    report.clear();
+
+    for level in line
+        .split(|b| *b == b' ')
+        // skip the extra space when levels are separated by more than one
+        .filter(|v| !v.is_empty())
+    {
+        let level = parse_u16(level).ok_or_else(|| {
+            ParseError::new(
+                2,
+                n,
+                column_of(line, level),
+                String::from_utf8_lossy(level),
+                "a level between 0 and 65535",
+            )
+        })?;
+        report.push(level);
+    }
 
-    res
+    Ok(())
 }
 
-fn parse_u16(v: &[u8]) -> u16 {
-    // In our problem input, we only have 1 or 2 digit numbers. Optimise for that.
-    match v.len() {
+fn parse_u16(v: &[u8]) -> Option<u16> {
+    // In our problem input, we only have 1 or 2 digit numbers. Optimise for that, and only take
+    // the slow path for anything longer.
+    match v {
         // b'8' => 8_u16
-        1 => unsafe { (v.get_unchecked(0) - 48) as u16 },
+        [a] if a.is_ascii_digit() => Some((a - b'0') as u16),
         // b'85' => (8 * 10 + 5)_u16
-        2 => unsafe { (v.get_unchecked(0) - 48) as u16 * 10 + (v.get_unchecked(1) - 48) as u16 },
-        _ => panic!("Not parsing '{:?}' into a u16", v),
+        [a, b] if a.is_ascii_digit() && b.is_ascii_digit() => {
+            Some((a - b'0') as u16 * 10 + (b - b'0') as u16)
+        }
+        [_, _, ..] => v.iter().try_fold(0_u16, |acc, b| {
+            if b.is_ascii_digit() {
+                acc.checked_mul(10)?.checked_add((b - b'0') as u16)
+            } else {
+                None
+            }
+        }),
+        _ => None,
     }
 }
 
-#[aoc(day2, part1, naive)]
-fn part1_impl(input: &[Vec<u16>]) -> u16 {
+/// Parses a report: levels separated by single spaces.
+fn report(input: &mut &str) -> PResult<Vec<u16>> {
+    // Once we've seen a space, another level has to follow.
+    separated(
+        1..,
+        cut_err(dec_uint::<_, u16, _>.context(expect("a level between 0 and 65535"))),
+        ' ',
+    )
+    .parse_next(input)
+}
+
+/// Parses the input with a grammar, which unlike [`parse`] also rejects blank lines between the
+/// reports and extra whitespace between or after the levels.
+fn parse_strict(input: &str) -> Result<Vec<Vec<u16>>, ParseError> {
+    grammar::parse(2, input, grammar::lines(report))
+}
+
+#[aoc_generator(day2, part1, strict)]
+fn parse_strict_part1(input: &str) -> Result<Vec<Vec<u16>>, ParseError> {
+    parse_strict(input)
+}
+
+#[aoc_generator(day2, part2, strict)]
+fn parse_strict_part2(input: &str) -> Result<Vec<Vec<u16>>, ParseError> {
+    parse_strict(input)
+}
+
+// Like the naive variant, the strict one is registered with cargo-aoc, which can't pass a policy
+// in, so it solves the puzzle as set. Other policies go through `part1_with` and `part2_with`.
+#[aoc(day2, part1, strict)]
+fn part1_strict(input: &[Vec<u16>]) -> u64 {
+    part1_impl(input, SafetyPolicy::PUZZLE)
+}
+
+#[aoc(day2, part2, strict)]
+fn part2_strict(input: &[Vec<u16>]) -> u64 {
     input
         .iter()
-        .filter(|report| is_safe(report).is_none())
-        .count() as u16
+        .filter(|report| is_safe_removing(report, SafetyPolicy::PUZZLE, 1))
+        .count() as u64
 }
 
-fn is_safe(report: &[u16]) -> Option<usize> {
-    let mut direction: Option<std::cmp::Ordering> = None;
+/// Which way the levels of a safe report go.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    Increasing,
+    Decreasing,
+    /// Either way, as long as every step goes the same way.
+    Either,
+}
 
-    for (i, pair) in report.windows(2).enumerate() {
-        let diff = pair[0].abs_diff(pair[1]);
+impl Direction {
+    /// How each level must compare to the next, if that's decided before looking at the report.
+    fn ordering(self) -> Option<Ordering> {
+        match self {
+            Direction::Increasing => Some(Ordering::Less),
+            Direction::Decreasing => Some(Ordering::Greater),
+            Direction::Either => None,
+        }
+    }
+}
 
-        if !(1..=3).contains(&diff) {
-            return Some(i);
+/// What makes a report safe: how much each level can differ from the next, and which way they go.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SafetyPolicy {
+    /// The smallest change allowed between neighbouring levels that differ.
+    pub min_step: u16,
+    /// The largest change allowed between neighbouring levels.
+    pub max_step: u16,
+    pub direction: Direction,
+    /// Whether neighbouring levels can be the same. Steps between them don't go either way.
+    pub allow_equal: bool,
+}
+
+impl SafetyPolicy {
+    /// The puzzle's policy: levels all increasing or all decreasing, by 1 to 3 at a time.
+    pub const PUZZLE: SafetyPolicy = SafetyPolicy {
+        min_step: 1,
+        max_step: 3,
+        direction: Direction::Either,
+        allow_equal: false,
+    };
+
+    /// Returns true if going from level `a` to level `b` is safe in a report going `direction`,
+    /// as `a` compares to `b`.
+    fn is_safe_step(&self, a: u16, b: u16, direction: Ordering) -> bool {
+        match a.cmp(&b) {
+            Ordering::Equal => self.allow_equal,
+            way => way == direction && (self.min_step..=self.max_step).contains(&a.abs_diff(b)),
         }
+    }
+}
 
-        match pair[0].cmp(&pair[1]) {
-            std::cmp::Ordering::Less => match direction {
-                None => {
-                    direction = Some(std::cmp::Ordering::Less);
-                }
-                Some(std::cmp::Ordering::Less) => {}
-                _ => return Some(i),
-            },
-            std::cmp::Ordering::Equal => return Some(i),
-            std::cmp::Ordering::Greater => match direction {
-                Some(std::cmp::Ordering::Greater) => {}
-                None => direction = Some(std::cmp::Ordering::Greater),
-                _ => return Some(i),
-            },
+impl Default for SafetyPolicy {
+    fn default() -> Self {
+        SafetyPolicy::PUZZLE
+    }
+}
+
+#[aoc(day2, part1, naive)]
+fn part1_naive(input: &[Vec<u16>]) -> u64 {
+    part1_impl(input, SafetyPolicy::PUZZLE)
+}
+
+fn part1_impl(input: &[Vec<u16>], policy: SafetyPolicy) -> u64 {
+    input
+        .iter()
+        .filter(|report| is_safe(report, policy).is_none())
+        .count() as u64
+}
+
+/// Returns the index of the first unsafe step in the report, from level `i` to `i + 1`, if any.
+fn is_safe(report: &[u16], policy: SafetyPolicy) -> Option<usize> {
+    let mut direction = policy.direction.ordering();
+
+    for (i, pair) in report.windows(2).enumerate() {
+        // Equal levels don't say which way the report is going.
+        let way = match pair[0].cmp(&pair[1]) {
+            Ordering::Equal => Ordering::Equal,
+            way => *direction.get_or_insert(way),
+        };
+        if !policy.is_safe_step(pair[0], pair[1], way) {
+            return Some(i);
         }
     }
     None
 }
 
+/// Counts the reports that are safe, or are once a level next to the first unsafe step is
+/// removed. That's only enough for the puzzle's policy: with equal levels allowed, for one, the
+/// direction can be decided long before the report goes wrong. Other policies go through
+/// [`is_safe_removing`] instead.
 #[aoc(day2, part2, naive)]
-fn part2_impl(input: &[Vec<u16>]) -> u16 {
+fn part2_naive(input: &[Vec<u16>]) -> u64 {
+    let policy = SafetyPolicy::PUZZLE;
     input
         .iter()
-        .map(|report| match is_safe(report) {
+        .map(|report| match is_safe(report, policy) {
             None => 1,
             Some(i) => {
                 if i > 0 && i < report.len() {
                     let mut skip_previous_input = report.clone();
                     skip_previous_input.remove(i - 1);
                     // let skip_previous_input = &[&report[..(i - 1)], &report[i..]].concat();
-                    if is_safe(&skip_previous_input).is_none() {
+                    if is_safe(&skip_previous_input, policy).is_none() {
                         return 1;
                     }
                 }
@@ -89,14 +298,14 @@ fn part2_impl(input: &[Vec<u16>]) -> u16 {
                 let mut skip_input = report.clone();
                 skip_input.remove(i);
                 // let skip_input = &[&report[..i], &report[(i + 1)..]].concat();
-                if is_safe(&skip_input).is_none() {
+                if is_safe(&skip_input, policy).is_none() {
                     1
                 } else {
                     let skip_next = if i < report.len() - 1 {
                         let mut skip_next_input = report.clone();
                         skip_next_input.remove(i + 1);
                         // let skip_next_level = &[&report[..(i + 1)], &report[(i + 2)..]].concat();
-                        is_safe(&skip_next_input)
+                        is_safe(&skip_next_input, policy)
                     } else {
                         Some(i)
                     };
@@ -111,70 +320,174 @@ fn part2_impl(input: &[Vec<u16>]) -> u16 {
         .sum()
 }
 
-fn part1_inline(input: &str) -> u16 {
+fn part1_inline(input: &str, policy: SafetyPolicy) -> Result<u64, ParseError> {
     let mut levels = Vec::with_capacity(10);
-    input
-        .as_bytes()
-        .split(|b| *b == b'\n')
-        .filter(|v| !v.is_empty())
-        .filter(|line| {
-            levels.clear();
-            line.split(|b| *b == b' ')
-                .map(parse_u16)
-                .for_each(|v| levels.push(v));
+    let mut safe = 0;
 
-            is_safe(&levels).is_none()
-        })
-        .count() as u16
+    for (n, line) in input::lines(input).enumerate() {
+        if line.is_empty() {
+            continue;
+        }
+        read_report(n + 1, line.as_bytes(), &mut levels)?;
+
+        if is_safe(&levels, policy).is_none() {
+            safe += 1;
+        }
+    }
+
+    Ok(safe)
 }
 
-fn part2_inline(input: &str) -> u16 {
+fn part2_inline(input: &str, policy: SafetyPolicy, k: usize) -> Result<u64, ParseError> {
     let mut report = Vec::with_capacity(10);
-    input
-        .as_bytes()
-        .split(|b| *b == b'\n')
-        .filter(|v| !v.is_empty())
-        .filter(|line| {
-            report.clear();
-            line.split(|b| *b == b' ')
-                .map(parse_u16)
-                .for_each(|v| report.push(v));
-
-            if let Some(i) = is_safe(&report) {
-                if i > 0
-                    && i < report.len()
-                    && is_safe(&[&report[..(i - 1)], &report[i..]].concat()).is_none()
-                {
-                    return true;
-                }
+    let mut safe = 0;
 
-                if is_safe(&[&report[..i], &report[(i + 1)..]].concat()).is_none() {
-                    true
-                } else {
-                    let skip_next = if i < report.len() - 1 {
-                        is_safe(&[&report[..(i + 1)], &report[(i + 2)..]].concat())
-                    } else {
-                        Some(i)
-                    };
-                    skip_next.is_none()
-                }
-            } else {
-                true
+    for (n, line) in input::lines(input).enumerate() {
+        if line.is_empty() {
+            continue;
+        }
+        read_report(n + 1, line.as_bytes(), &mut report)?;
+
+        if is_safe_removing(&report, policy, k) {
+            safe += 1;
+        }
+    }
+
+    Ok(safe)
+}
+
+/// How many levels back [`is_safe_removing`] can look without allocating, which is enough to
+/// remove up to 15 levels.
+const WINDOW: usize = 16;
+
+/// Returns true if the report is safe under `policy`, or can be made safe by removing at most `k`
+/// levels, which takes `O(n·k)` steps for `n` levels. Only allocates when `k` is [`WINDOW`] or
+/// more.
+pub fn is_safe_removing(report: &[u16], policy: SafetyPolicy, k: usize) -> bool {
+    // Any one level on its own is safe.
+    if report.len() <= k + 1 {
+        return true;
+    }
+
+    let mut stack = [0; WINDOW];
+    let mut heap = Vec::new();
+    let fewest = if k < WINDOW {
+        &mut stack[..=k]
+    } else {
+        heap.resize(k + 1, 0);
+        &mut heap[..]
+    };
+
+    [Ordering::Less, Ordering::Greater]
+        .into_iter()
+        .filter(|direction| policy.direction.ordering().is_none_or(|d| d == *direction))
+        .any(|direction| is_safe_going(report, policy, k, direction, fewest))
+}
+
+/// Returns true if removing at most `k` levels leaves every step safe under `policy`, going in
+/// `direction`. Works through the levels keeping, for each of the last `k + 1`, the fewest levels
+/// we'd have to remove before it for the report to be safe up to there with it kept.
+fn is_safe_going(
+    report: &[u16],
+    policy: SafetyPolicy,
+    k: usize,
+    direction: Ordering,
+    fewest: &mut [usize],
+) -> bool {
+    let n = report.len();
+    let safe_step = |a: u16, b: u16| policy.is_safe_step(a, b, direction);
+
+    for i in 0..n {
+        // At worst, we remove every level before this one.
+        let mut removed = i;
+        for j in i.saturating_sub(k + 1)..i {
+            if safe_step(report[j], report[i]) {
+                removed = removed.min(fewest[j % (k + 1)] + (i - j - 1));
             }
-        })
-        .count() as u16
+        }
+
+        if removed + (n - 1 - i) <= k {
+            return true;
+        }
+        fewest[i % (k + 1)] = removed;
+    }
+
+    false
+}
+
+/// Counts the reports from `input` that pass `safe`, holding only one line in memory at a time.
+fn count_safe(mut input: impl BufRead, safe: impl Fn(&[u16]) -> bool) -> Result<u64, ReadError> {
+    let mut line = String::with_capacity(32);
+    let mut report = Vec::with_capacity(10);
+    let mut count = 0;
+
+    for n in 1.. {
+        line.clear();
+        if input.read_line(&mut line)? == 0 {
+            break;
+        }
+
+        let line = input::trim_line(&line);
+        if line.is_empty() {
+            continue;
+        }
+        read_report(n, line.as_bytes(), &mut report)?;
+
+        if safe(&report) {
+            count += 1;
+        }
+    }
+
+    Ok(count)
+}
+
+pub fn part1(input: &str) -> Result<u64, SolveError> {
+    part1_with(input, SafetyPolicy::PUZZLE)
+}
+
+pub fn part2(input: &str) -> Result<u64, SolveError> {
+    part2_with(input, SafetyPolicy::PUZZLE, 1)
+}
+
+/// Like [`part1`], but judges the reports by `policy` rather than the puzzle's rules.
+pub fn part1_with(input: &str, policy: SafetyPolicy) -> Result<u64, SolveError> {
+    Ok(part1_inline(input, policy)?)
+}
+
+/// Like [`part2`], but judges the reports by `policy`, and counts those that can be made safe by
+/// removing up to `k` levels rather than just one.
+pub fn part2_with(input: &str, policy: SafetyPolicy, k: usize) -> Result<u64, SolveError> {
+    Ok(part2_inline(input, policy, k)?)
+}
+
+/// Like [`part1`], but reads the reports a line at a time, so the input can be any size.
+pub fn part1_reader(input: impl BufRead) -> Result<u64, ReadError> {
+    part1_reader_with(input, SafetyPolicy::PUZZLE)
+}
+
+/// Like [`part2`], but reads the reports a line at a time, so the input can be any size.
+pub fn part2_reader(input: impl BufRead) -> Result<u64, ReadError> {
+    part2_reader_with(input, SafetyPolicy::PUZZLE, 1)
 }
 
-pub fn part1(input: &str) -> u16 {
-    part1_inline(input)
+/// Like [`part1_with`], but reads the reports a line at a time.
+pub fn part1_reader_with(input: impl BufRead, policy: SafetyPolicy) -> Result<u64, ReadError> {
+    count_safe(input, |report| is_safe(report, policy).is_none())
 }
 
-pub fn part2(input: &str) -> u16 {
-    part2_inline(input)
+/// Like [`part2_with`], but reads the reports a line at a time.
+pub fn part2_reader_with(
+    input: impl BufRead,
+    policy: SafetyPolicy,
+    k: usize,
+) -> Result<u64, ReadError> {
+    count_safe(input, |report| is_safe_removing(report, policy, k))
 }
 
 #[cfg(test)]
 mod tests {
+    use proptest::prelude::*;
+
     use super::*;
 
     #[test]
@@ -188,7 +501,7 @@ mod tests {
 8 6 4 4 1
 1 3 6 7 9"
             ),
-            2
+            Ok(2)
         )
     }
 
@@ -203,15 +516,202 @@ mod tests {
 8 6 4 4 1
 1 3 6 7 9"
             ),
-            4
+            Ok(4)
         );
     }
 
     #[test]
     fn part2_boundary_conditions() {
-        assert_eq!(part2("1 3 6 7 9 5"), 1, "last level can be dampened");
-        assert_eq!(part2("9 1 2 3 4 5"), 1, "first level can be dampened");
-        assert_eq!(part2("5 2 3 4 5"), 1, "first level can be dampened");
-        assert_eq!(part2("1 2 2 4 7"), 1, "second level can be dampened");
+        assert_eq!(part2("1 3 6 7 9 5"), Ok(1), "last level can be dampened");
+        assert_eq!(part2("9 1 2 3 4 5"), Ok(1), "first level can be dampened");
+        assert_eq!(part2("5 2 3 4 5"), Ok(1), "first level can be dampened");
+        assert_eq!(part2("1 2 2 4 7"), Ok(1), "second level can be dampened");
+    }
+
+    #[test]
+    fn removes_up_to_k_levels() {
+        let example = "7 6 4 2 1\n1 2 7 8 9\n9 7 6 2 1\n1 3 2 4 5\n8 6 4 4 1\n1 3 6 7 9";
+        let puzzle = SafetyPolicy::PUZZLE;
+        assert_eq!(part2_with(example, puzzle, 0), Ok(2));
+        assert_eq!(part2_with(example, puzzle, 1), Ok(4));
+        // Removing two levels is enough to drop 1 2 from 1 2 7 8 9, and 2 1 from 9 7 6 2 1.
+        assert_eq!(part2_with(example, puzzle, 2), Ok(6));
+        assert_eq!(
+            part2_reader_with(example.as_bytes(), puzzle, 2).unwrap(),
+            6
+        );
+
+        assert!(is_safe_removing(&[], puzzle, 0));
+        assert!(is_safe_removing(&[5], puzzle, 0));
+        assert!(!is_safe_removing(&[5, 5], puzzle, 0));
+        assert!(is_safe_removing(&[1, 9, 9, 9, 2, 3], puzzle, 3));
+        assert!(!is_safe_removing(&[1, 9, 9, 9, 2, 3], puzzle, 2));
+
+        // Removing more levels than fit in the window on the stack.
+        let mut report = vec![1, 2, 3];
+        report.extend([50; 20]);
+        report.extend([4, 5]);
+        assert!(is_safe_removing(&report, puzzle, 20));
+        assert!(!is_safe_removing(&report, puzzle, 19));
+    }
+
+    #[test]
+    fn judges_by_policy() {
+        let example = "7 6 4 2 1\n1 2 7 8 9\n9 7 6 2 1\n1 3 2 4 5\n8 6 4 4 1\n1 3 6 7 9";
+        let puzzle = SafetyPolicy::default();
+        let policy = |policy| (part1_with(example, policy), part2_with(example, policy, 1));
+
+        assert_eq!(policy(puzzle), (Ok(2), Ok(4)));
+        let increasing = SafetyPolicy {
+            direction: Direction::Increasing,
+            ..puzzle
+        };
+        assert_eq!(policy(increasing), (Ok(1), Ok(2)));
+        let decreasing = SafetyPolicy {
+            direction: Direction::Decreasing,
+            ..puzzle
+        };
+        assert_eq!(policy(decreasing), (Ok(1), Ok(2)));
+        // 1 2 7 8 9 and 9 7 6 2 1 have steps of 5 and 4.
+        let wider = SafetyPolicy {
+            max_step: 5,
+            ..puzzle
+        };
+        assert_eq!(policy(wider), (Ok(4), Ok(6)));
+        let equal = SafetyPolicy {
+            allow_equal: true,
+            ..puzzle
+        };
+        assert_eq!(policy(equal), (Ok(3), Ok(4)));
+        let bigger = SafetyPolicy {
+            min_step: 2,
+            ..puzzle
+        };
+        // Removing the 7 from 1 3 6 7 9, or a 4 from 8 6 4 4 1, leaves steps of 2 or 3.
+        assert_eq!(policy(bigger), (Ok(0), Ok(2)));
+
+        // With equal levels allowed, the first step that changes decides the direction, so it can
+        // be far from where the report goes wrong.
+        assert!(!is_safe_removing(&[1, 2, 2, 2, 1, 0], equal, 0));
+        assert!(is_safe_removing(&[1, 2, 2, 2, 1, 0], equal, 1));
+        assert_eq!(
+            part1_reader_with("5 5 6\n6 6 6".as_bytes(), equal).unwrap(),
+            2
+        );
+    }
+
+    /// Checks every way of removing up to `k` levels.
+    fn is_safe_removing_any(report: &[u16], policy: SafetyPolicy, k: usize) -> bool {
+        (0_u32..1 << report.len())
+            .filter(|removed| removed.count_ones() as usize <= k)
+            .any(|removed| {
+                let kept = (0..report.len())
+                    .filter(|i| removed & (1 << i) == 0)
+                    .map(|i| report[i])
+                    .collect::<Vec<_>>();
+                is_safe(&kept, policy).is_none()
+            })
+    }
+
+    fn policies() -> impl Strategy<Value = SafetyPolicy> {
+        let directions = prop_oneof![
+            Just(Direction::Increasing),
+            Just(Direction::Decreasing),
+            Just(Direction::Either),
+        ];
+        (0..3_u16, 0..5_u16, directions, any::<bool>()).prop_map(
+            |(min_step, max_step, direction, allow_equal)| SafetyPolicy {
+                min_step,
+                max_step,
+                direction,
+                allow_equal,
+            },
+        )
+    }
+
+    proptest! {
+        #[test]
+        fn removes_like_trying_every_way(
+            report in prop::collection::vec(0..12_u16, 0..10),
+            policy in policies(),
+            k in 0..4_usize,
+        ) {
+            prop_assert_eq!(
+                is_safe_removing(&report, policy, k),
+                is_safe_removing_any(&report, policy, k)
+            );
+        }
+
+        #[test]
+        fn removing_one_matches_naive(report in prop::collection::vec(0..12_u16, 0..10)) {
+            let naive = part2_naive(std::slice::from_ref(&report)) == 1;
+            prop_assert_eq!(is_safe_removing(&report, SafetyPolicy::PUZZLE, 1), naive);
+        }
+    }
+
+    #[test]
+    fn parses_longer_levels() {
+        assert_eq!(parse_u16(b"7"), Some(7));
+        assert_eq!(parse_u16(b"85"), Some(85));
+        assert_eq!(parse_u16(b"100"), Some(100));
+        assert_eq!(parse_u16(b"65535"), Some(65535));
+        assert_eq!(parse_u16(b"65536"), None);
+        assert_eq!(parse_u16(b"1x"), None);
+        assert_eq!(parse_u16(b""), None);
+        assert_eq!(part1("100 101 103"), Ok(1));
+    }
+
+    #[test]
+    fn reports_malformed_levels() {
+        let err = ParseError::new(2, 2, 3, "x", "a level between 0 and 65535");
+        assert_eq!(part1("1 2 3\n1 x 3"), Err(err.clone().into()));
+        assert_eq!(part2("1 2 3\n1 x 3"), Err(err.clone().into()));
+        assert_eq!(parse("1 2 3\n1 x 3"), Err(err.clone()));
+        assert!(matches!(
+            part1_reader("1 2 3\n1 x 3\n".as_bytes()),
+            Err(ReadError::Parse(e)) if e == err
+        ));
+    }
+
+    #[test]
+    fn handles_line_endings() {
+        let example = "7 6 4 2 1\n1 2 7 8 9\n9 7 6 2 1\n1 3 2 4 5\n8 6 4 4 1\n1 3 6 7 9";
+        for input in crate::input::variants(example) {
+            assert_eq!(part1(&input), Ok(2), "{input:?}");
+            assert_eq!(part2(&input), Ok(4), "{input:?}");
+            let naive = parse(&input).unwrap();
+            assert_eq!((part1_naive(&naive), part2_naive(&naive)), (2, 4), "{input:?}");
+            assert_eq!(part1_reader(input.as_bytes()).unwrap(), 2, "{input:?}");
+            assert_eq!(part2_reader(input.as_bytes()).unwrap(), 4, "{input:?}");
+        }
+    }
+
+    #[test]
+    fn strict_parser_checks_everything() {
+        let example = "7 6 4 2 1\n1 2 7 8 9\n9 7 6 2 1\n1 3 2 4 5\n8 6 4 4 1\n1 3 6 7 9";
+        assert_eq!(parse_strict(example), parse(example));
+        assert_eq!(parse_strict("1 2\r\n3 4\r\n"), Ok(vec![vec![1, 2], vec![3, 4]]));
+
+        let level = "a level between 0 and 65535";
+        for (input, err) in [
+            ("1 2 3\n1 x 3", ParseError::new(2, 2, 3, "x", level)),
+            ("1 65536", ParseError::new(2, 1, 3, "65536", level)),
+            ("1  2", ParseError::new(2, 1, 3, "", level)),
+            ("1 2 \n3", ParseError::new(2, 1, 5, "", level)),
+            ("1 2\n\n3", ParseError::new(2, 2, 1, "", level)),
+            ("1 2\t\n3", ParseError::new(2, 1, 4, "", "the end of the line")),
+        ] {
+            assert_eq!(parse_strict(input), Err(err), "{input:?}");
+        }
+    }
+
+    #[test]
+    fn reads_reports_a_line_at_a_time() {
+        let input = "7 6 4 2 1\n1 2 7 8 9\n9 7 6 2 1\n1 3 2 4 5\n8 6 4 4 1\n1 3 6 7 9\n";
+        // A tiny buffer makes sure lines spanning reads are put back together.
+        let reader = || std::io::BufReader::with_capacity(3, input.as_bytes());
+        assert_eq!(part1_reader(reader()).unwrap(), 2);
+        assert_eq!(part2_reader(reader()).unwrap(), 4);
+        assert_eq!(part1_reader(&b""[..]).unwrap(), 0);
     }
 }
@@ -1,23 +1,66 @@
+use std::io::{BufRead, ErrorKind};
+
 use aoc_runner_derive::{aoc, aoc_generator};
 use winnow::{
-    ascii::dec_uint,
     combinator::{delimited, separated_pair},
     stream::FindSlice,
+    token::take_while,
     PResult, Parser,
 };
 
+use crate::{checked::Answer, OverflowError, ParseError, ReadError, Solution, SolveError};
+
+/// Each part scans the raw input in a single pass, so there's nothing to parse up front.
+pub struct Day3;
+
+impl Solution for Day3 {
+    const DAY: u8 = 3;
+
+    type Input<'a> = &'a str;
+    type Output1 = u64;
+    type Output2 = u64;
+
+    fn parse(input: &str) -> Result<Self::Input<'_>, ParseError> {
+        Ok(input)
+    }
+
+    fn part1(input: &Self::Input<'_>) -> Result<u64, SolveError> {
+        Ok(parse_part1(input)?)
+    }
+
+    fn part2(input: &Self::Input<'_>) -> Result<u64, SolveError> {
+        Ok(parse_part2(input)?)
+    }
+}
+
 #[derive(Eq, PartialEq, Debug)]
 struct Mul((u32, u32));
 
 impl Mul {
-    fn eval(&self) -> u32 {
-        self.0 .0 * self.0 .1
+    /// Multiplies the numbers, which can't overflow as a `u64`.
+    fn eval(&self) -> u64 {
+        self.0 .0 as u64 * self.0 .1 as u64
     }
 }
 
+const SUM: Answer = Answer::new(3, "the sum of the multiplications");
+
+/// A `u32` has at most 10 digits, so an argument with more is taken as corruption rather than part
+/// of an instruction, even if it's padded with zeros. Reading the memory whole or a chunk at a time
+/// both cap arguments like this, so they always agree, and the chunks never need to hold on to more
+/// than a few instructions' worth of digits.
+const MAX_DIGITS: usize = 10;
+
+/// Parses a number of up to [`MAX_DIGITS`] digits, which must fit in a `u32`.
+fn number(i: &mut &[u8]) -> PResult<u32> {
+    take_while(1..=MAX_DIGITS, '0'..='9')
+        .parse_to()
+        .parse_next(i)
+}
+
 /// Parses something like 123,456
 fn number_pair(i: &mut &[u8]) -> PResult<(u32, u32)> {
-    separated_pair(dec_uint, ',', dec_uint).parse_next(i)
+    separated_pair(number, ',', number).parse_next(i)
 }
 
 /// Parses a mul instruction like `mul(123, 44)`
@@ -25,94 +68,180 @@ fn mul(i: &mut &[u8]) -> PResult<Mul> {
     delimited("mul(", number_pair, ')').map(Mul).parse_next(i)
 }
 
-#[aoc_generator(day3, part1)]
-fn parse_part1(input: &str) -> u32 {
-    let mut res = 0;
+/// Adds up the `mul` instructions in corrupted memory, which may arrive a chunk at a time.
+struct Scanner {
+    /// Whether to follow `do()` and `don't()`, as part 2 does.
+    conditionals: bool,
+    enabled: bool,
+    sum: u64,
+}
 
-    let mut i = 0;
+impl Scanner {
+    fn new(conditionals: bool) -> Self {
+        Scanner {
+            conditionals,
+            enabled: true,
+            sum: 0,
+        }
+    }
 
-    let input = input.as_bytes();
+    /// Scans as much of `input` as it can, returning how many bytes it got through. Unless this is
+    /// the end of the input, it stops short of anything that might be an instruction cut off by
+    /// the end of the chunk, which should be scanned again with more input after it.
+    fn scan(&mut self, input: &[u8], end: bool) -> Result<usize, OverflowError> {
+        let mut i = 0;
 
-    while i < input.len() {
-        let haystack = &input[i..];
+        while i < input.len() {
+            if !self.conditionals {
+                let haystack = &input[i..];
+
+                // Skip to the next thing that looks like an expression prefix "mul("
+                match haystack.find_slice(&b"mul("[..]) {
+                    Some(index) => i += index.start,
+                    // The last few bytes could still be the start of one.
+                    None if end => return Ok(input.len()),
+                    None => return Ok(input.len().saturating_sub(3).max(i)),
+                }
+            }
+
+            if !end && incomplete(&input[i..]) {
+                return Ok(i);
+            }
+
+            if self.conditionals {
+                if input[i..].starts_with(b"do()") {
+                    self.enabled = true;
+                    i += 4;
+                    continue;
+                }
+                if input[i..].starts_with(b"don't()") {
+                    self.enabled = false;
+                    i += 7;
+                    continue;
+                }
+            }
 
-        // Skip to the next thing that looks like an expression prefix "mul("
-        if let Some(index) = haystack.find_slice(&b"mul("[..]) {
-            i += index.start;
             let mut suffix = &input[i..];
             let n = suffix.len();
 
             // and then try to parse it as an expression
-            if let Ok(mul) = mul(&mut suffix) {
-                res += mul.eval();
-                let chars_parsed = n - suffix.len();
-                i += chars_parsed;
-                continue;
+            if self.enabled {
+                if let Ok(mul) = mul(&mut suffix) {
+                    self.sum = SUM.add(self.sum, mul.eval())?;
+                    let chars_parsed = n - suffix.len();
+                    i += chars_parsed;
+                    continue;
+                }
             }
+
+            i += 1;
         }
 
-        i += 1;
+        Ok(i)
     }
-
-    res
 }
 
-#[aoc(day3, part1)]
-fn part1_impl(input: &u32) -> u32 {
-    *input
+/// Returns true if `rest` could still turn out to be an instruction, given more input after it.
+fn incomplete(rest: &[u8]) -> bool {
+    match rest.strip_prefix(b"mul(") {
+        // `mul(` followed by nothing but digits and at most one comma isn't finished yet, unless
+        // an argument is already too long, which also stops us holding on to endless digits.
+        Some(args) => {
+            args.iter().all(|b| b.is_ascii_digit() || *b == b',')
+                && args.iter().filter(|b| **b == b',').count() <= 1
+                && args.split(|b| *b == b',').all(|arg| arg.len() <= MAX_DIGITS)
+        }
+        None => [&b"mul("[..], b"do()", b"don't()"]
+            .iter()
+            .any(|token| token.len() > rest.len() && token.starts_with(rest)),
+    }
 }
 
-#[aoc_generator(day3, part2)]
-fn parse_part2(input: &str) -> u32 {
-    let mut res = 0;
-
-    let mut i = 0;
+/// Feeds `input` through `scanner` a chunk at a time, keeping back only the bytes that might be
+/// the start of an instruction.
+fn scan_reader(mut input: impl BufRead, mut scanner: Scanner) -> Result<u64, ReadError> {
+    let mut pending = Vec::new();
 
-    let mut enabled = true;
+    loop {
+        let chunk = match input.fill_buf() {
+            Ok(chunk) => chunk,
+            Err(e) if e.kind() == ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e.into()),
+        };
+        let end = chunk.is_empty();
+        let len = chunk.len();
 
-    let input = input.as_bytes();
+        scan_chunk(&mut scanner, &mut pending, chunk, end)?;
+        input.consume(len);
 
-    while i < input.len() {
-        if input[i..].starts_with(b"do()") {
-            enabled = true;
-            i += 4;
-            continue;
-        }
-        if input[i..].starts_with(b"don't()") {
-            enabled = false;
-            i += 7;
-            continue;
+        if end {
+            return Ok(scanner.sum);
         }
+    }
+}
 
-        let mut suffix = &input[i..];
-        let n = suffix.len();
+/// Scans the next `chunk` of input after what's `pending` from the last, leaving in `pending` only
+/// the bytes that might be the start of an instruction.
+fn scan_chunk(
+    scanner: &mut Scanner,
+    pending: &mut Vec<u8>,
+    chunk: &[u8],
+    end: bool,
+) -> Result<(), OverflowError> {
+    if pending.is_empty() {
+        // Save a copy when nothing's left over from the last chunk.
+        let done = scanner.scan(chunk, end)?;
+        pending.extend_from_slice(&chunk[done..]);
+    } else {
+        pending.extend_from_slice(chunk);
+        let done = scanner.scan(pending, end)?;
+        pending.drain(..done);
+    }
+    Ok(())
+}
 
-        if enabled {
-            if let Ok(mul) = mul(&mut suffix) {
-                res += mul.eval();
-                let chars_parsed = n - suffix.len();
-                i += chars_parsed;
-                continue;
-            }
-        }
+#[aoc_generator(day3, part1)]
+fn parse_part1(input: &str) -> Result<u64, OverflowError> {
+    let mut scanner = Scanner::new(false);
+    scanner.scan(input.as_bytes(), true)?;
+    Ok(scanner.sum)
+}
 
-        i += 1;
-    }
+#[aoc(day3, part1)]
+fn part1_impl(input: &u64) -> u64 {
+    *input
+}
 
-    res
+#[aoc_generator(day3, part2)]
+fn parse_part2(input: &str) -> Result<u64, OverflowError> {
+    let mut scanner = Scanner::new(true);
+    scanner.scan(input.as_bytes(), true)?;
+    Ok(scanner.sum)
 }
 
 #[aoc(day3, part2)]
-fn part2_impl(input: &u32) -> u32 {
+fn part2_impl(input: &u64) -> u64 {
     *input
 }
 
-pub fn part1(input: &str) -> u32 {
-    part1_impl(&parse_part1(input))
+// Corrupted memory is expected, so there's no such thing as malformed input today. The answer can
+// still overflow, though. Arguments longer than `MAX_DIGITS` don't count, even with leading zeros.
+pub fn part1(input: &str) -> Result<u64, SolveError> {
+    Ok(part1_impl(&parse_part1(input)?))
+}
+
+pub fn part2(input: &str) -> Result<u64, SolveError> {
+    Ok(part2_impl(&parse_part2(input)?))
 }
 
-pub fn part2(input: &str) -> u32 {
-    part2_impl(&parse_part2(input))
+/// Like [`part1`], but reads the memory a chunk at a time, so it can be any size.
+pub fn part1_reader(input: impl BufRead) -> Result<u64, ReadError> {
+    scan_reader(input, Scanner::new(false))
+}
+
+/// Like [`part2`], but reads the memory a chunk at a time, so it can be any size.
+pub fn part2_reader(input: impl BufRead) -> Result<u64, ReadError> {
+    scan_reader(input, Scanner::new(true))
 }
 
 #[cfg(test)]
@@ -123,7 +252,7 @@ mod tests {
     fn part1_example() {
         assert_eq!(
             part1("xmul(2,4)%&mul[3,7]!@^do_not_mul(5,5)+mul(32,64]then(mul(11,8)mul(8,5))"),
-            161
+            Ok(161)
         );
     }
 
@@ -131,7 +260,86 @@ mod tests {
     fn part2_example() {
         assert_eq!(
             part2("xmul(2,4)&mul[3,7]!^don't()_mul(5,5)+mul(32,64](mul(11,8)undo()?mul(8,5))"),
-            48
+            Ok(48)
         );
     }
+
+    #[test]
+    fn ignores_line_endings() {
+        // Instructions can't span lines, so there's nothing to normalise today.
+        let example = "xmul(2,4)&mul[3,7]!^don't()_mul(5,5)\n+mul(32,64](mul(11,8)undo()?mul(8,5))";
+        for input in crate::input::variants(example) {
+            assert_eq!(part1(&input), Ok(161), "{input:?}");
+            assert_eq!(part2(&input), Ok(48), "{input:?}");
+        }
+    }
+
+    #[test]
+    fn reads_memory_in_chunks() {
+        let inputs = [
+            "xmul(2,4)%&mul[3,7]!@^do_not_mul(5,5)+mul(32,64]then(mul(11,8)mul(8,5))",
+            "xmul(2,4)&mul[3,7]!^don't()_mul(5,5)+mul(32,64](mul(11,8)undo()?mul(8,5))",
+            "mul(1234,5678)don't()mul(2,2)do()mul(3,3)mumul(4,4)mul(5,5",
+            "mul(",
+            "",
+        ];
+
+        // Every chunk size, so instructions get split at every possible point.
+        for input in inputs {
+            for capacity in 1..=input.len().max(1) {
+                let reader = || std::io::BufReader::with_capacity(capacity, input.as_bytes());
+                assert_eq!(
+                    part1_reader(reader()).unwrap(),
+                    part1(input).unwrap(),
+                    "{input:?} in chunks of {capacity}"
+                );
+                assert_eq!(
+                    part2_reader(reader()).unwrap(),
+                    part2(input).unwrap(),
+                    "{input:?} in chunks of {capacity}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn waits_for_unfinished_instructions() {
+        assert!(incomplete(b"mu"));
+        assert!(incomplete(b"don"));
+        assert!(incomplete(b"mul(12,"));
+        assert!(incomplete(b"mul(12,345"));
+        assert!(!incomplete(b"mul(12,3)"));
+        assert!(!incomplete(b"mul(1,2,"));
+        assert!(incomplete(b"mul(1234567890,"));
+        assert!(!incomplete(b"mul(12345678901"));
+        assert!(!incomplete(b"mul(1,12345678901"));
+        assert!(!incomplete(b"do()"));
+        assert!(!incomplete(b"x"));
+    }
+
+    #[test]
+    fn drops_endless_digits() {
+        let input = format!("mul({}mul(2,3)", "1".repeat(100_000));
+        let capacity = 64;
+
+        for conditionals in [false, true] {
+            let mut reader = std::io::BufReader::with_capacity(capacity, input.as_bytes());
+            let (mut scanner, mut pending) = (Scanner::new(conditionals), Vec::new());
+            loop {
+                let chunk = reader.fill_buf().unwrap();
+                let (end, len) = (chunk.is_empty(), chunk.len());
+                scan_chunk(&mut scanner, &mut pending, chunk, end).unwrap();
+                reader.consume(len);
+                // At most an unfinished `mul(` with both its arguments, and the chunk after it.
+                assert!(pending.len() <= "mul(,".len() + 2 * MAX_DIGITS + capacity);
+                if end {
+                    break;
+                }
+            }
+            assert_eq!(scanner.sum, 6);
+        }
+        assert_eq!(part1(&input), Ok(6));
+        // The cap holds when the memory is read whole, too, so the answers always agree.
+        assert_eq!(part1("mul(00000000001,2)mul(0000000001,2)"), Ok(2));
+    }
 }
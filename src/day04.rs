@@ -1,39 +1,70 @@
-use aoc_runner_derive::aoc;
+use aoc_runner_derive::{aoc, aoc_generator};
 
-// No generator today because lifetimes. See https://github.com/gobanos/cargo-aoc/issues/20
-fn parse(input: &str) -> Vec<&[u8]> {
-    input.as_bytes().split(|b| *b == b'\n').collect::<Vec<_>>()
-}
+use crate::{
+    grid::Grid,
+    point::{Direction, Point},
+    ParseError, Solution, SolveError,
+};
 
-#[aoc(day4, part1)]
-pub fn part1(input: &str) -> u16 {
-    let m = parse(input);
+pub struct Day4;
 
-    let mut res = 0;
+impl Solution for Day4 {
+    const DAY: u8 = 4;
 
-    for r in 0..m.len() {
-        for c in 0..m[0].len() {
-            if m[r][c] == b'X' {
-                res += count_xmas(&m, r, c)
-            }
-        }
+    type Input<'a> = Grid<u8>;
+    type Output1 = u64;
+    type Output2 = u64;
+
+    fn parse(input: &str) -> Result<Self::Input<'_>, ParseError> {
+        parse(input)
     }
 
-    res
+    fn part1(input: &Self::Input<'_>) -> Result<u64, SolveError> {
+        Ok(part1_impl(input))
+    }
+
+    fn part2(input: &Self::Input<'_>) -> Result<u64, SolveError> {
+        Ok(part2_impl(input))
+    }
 }
 
-#[aoc(day4, part2)]
-pub fn part2(input: &str) -> u16 {
-    let m = parse(input);
+#[aoc_generator(day4)]
+fn parse(input: &str) -> Result<Grid<u8>, ParseError> {
+    Grid::parse(4, input)
+}
+
+pub fn part1(input: &str) -> Result<u64, SolveError> {
+    Ok(part1_impl(&parse(input)?))
+}
+
+#[aoc(day4, part1)]
+fn part1_impl(m: &Grid<u8>) -> u64 {
+    m.iter()
+        .filter(|(_, cell)| **cell == b'X')
+        .map(|(pos, _)| count_xmas(m, pos))
+        .sum()
+}
 
+pub fn part2(input: &str) -> Result<u64, SolveError> {
+    Ok(part2_impl(&parse(input)?))
+}
+
+/// Checks that the input is a rectangular grid of letters, returning every problem.
+pub fn validate(input: &str) -> Vec<ParseError> {
+    Grid::validate(4, input, |b| b.is_ascii_uppercase(), "a capital letter")
+}
+
+#[aoc(day4, part2)]
+fn part2_impl(m: &Grid<u8>) -> u64 {
     let mut res = 0;
 
     // 'A' must be inside the border in valid arrangements, so we can cut off a couple of
     // iterations in both dimensions
-    for r in 1..m.len() - 1 {
-        for c in 1..m[0].len() - 1 {
-            if m[r][c] == b'A' {
-                res += is_x_mas(&m, r, c) as u16
+    for r in 1..m.height() - 1 {
+        for c in 1..m.width() - 1 {
+            let pos = Point::new(r, c);
+            if m[pos] == b'A' {
+                res += is_x_mas(m, pos) as u64
             }
         }
     }
@@ -41,40 +72,23 @@ pub fn part2(input: &str) -> u16 {
     res
 }
 
-fn get(m: &[&[u8]], r: usize, c: usize) -> u8 {
-    *m.get(r).and_then(|row| row.get(c)).unwrap_or(&b'_')
-}
-
 /// Returns the count, given we have an X, of the surrounding squares of many MAS sequences we have hanging off that in every direction.
-fn count_xmas(m: &[&[u8]], r: usize, c: usize) -> u16 {
-    [
-        (0_i16, -1_i16),
-        (-1, 0),
-        (0, 1),
-        (1, 0),
-        (1, 1),
-        (-1, 1),
-        (1, -1),
-        (-1, -1),
-    ]
-    .iter()
-    .filter(|(dr, dc)| {
-        (1..4).all(|i| {
-            let (rr, cc) = (
-                r.wrapping_add((dr * i) as usize),
-                c.wrapping_add((dc * i) as usize),
-            );
-            get(m, rr, cc) == b"XMAS"[i as usize]
-        })
-    })
-    .count() as u16
+fn count_xmas(m: &Grid<u8>, pos: Point) -> u64 {
+    Direction::ALL
+        .into_iter()
+        .filter(|dir| m.ray(pos, *dir).map(|(_, v)| *v).take(4).eq(*b"XMAS"))
+        .count() as u64
 }
 
 /// Returns true if, given we have an A, search the surrounding corners of the immediate square to check that we have an MS or SM across the diagonals.
-fn is_x_mas(m: &[&[u8]], r: usize, c: usize) -> bool {
-    let w1 = [get(m, r - 1, c - 1), get(m, r + 1, c + 1)];
-    let w2 = [get(m, r - 1, c + 1), get(m, r + 1, c - 1)];
-    [w1, w2].iter().all(|w| w == b"MS" || w == b"SM")
+fn is_x_mas(m: &Grid<u8>, pos: Point) -> bool {
+    let ms = |from: Direction| {
+        matches!(
+            (m.neighbour(pos, from), m.neighbour(pos, from.reverse())),
+            (Some(b'M'), Some(b'S')) | (Some(b'S'), Some(b'M'))
+        )
+    };
+    ms(Direction::UpLeft) && ms(Direction::UpRight)
 }
 
 #[cfg(test)]
@@ -95,11 +109,44 @@ MXMXAXMASX";
 
     #[test]
     fn part1_example() {
-        assert_eq!(part1(INPUT), 18);
+        assert_eq!(part1(INPUT), Ok(18));
     }
 
     #[test]
     fn part2_example() {
-        assert_eq!(part2(INPUT), 9);
+        assert_eq!(part2(INPUT), Ok(9));
+    }
+
+    #[test]
+    fn handles_line_endings() {
+        for input in crate::input::variants(INPUT) {
+            assert_eq!(part1(&input), Ok(18), "{input:?}");
+            assert_eq!(part2(&input), Ok(9), "{input:?}");
+        }
+    }
+
+    #[test]
+    fn validates_the_grid() {
+        assert_eq!(validate(INPUT), []);
+        assert_eq!(
+            validate("XMAS\nXM\nXmA5"),
+            [
+                ParseError::new(4, 2, 1, "XM", "a row as long as the first"),
+                ParseError::new(4, 3, 2, "m", "a capital letter"),
+                ParseError::new(4, 3, 4, "5", "a capital letter"),
+            ]
+        );
+    }
+
+    #[test]
+    fn rejects_ragged_grids() {
+        assert_eq!(
+            part1("XMAS\nXMA\nXMAS"),
+            Err(ParseError::new(4, 2, 1, "XMA", "a row as long as the first").into())
+        );
+        assert_eq!(
+            part2(""),
+            Err(ParseError::new(4, 1, 1, "", "a row of cells").into())
+        );
     }
 }
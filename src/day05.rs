@@ -1,73 +1,367 @@
+use std::io::BufRead;
+
 use aoc_runner_derive::{aoc, aoc_generator};
 use rustc_hash::FxHashMap as HashMap;
 use rustc_hash::FxHashSet as HashSet;
+use winnow::{
+    ascii::{dec_uint, line_ending},
+    combinator::{cut_err, eof, not, preceded, repeat_till, separated, terminated},
+    PResult, Parser,
+};
+
+use crate::{
+    checked::Answer,
+    column_of,
+    grammar::{self, expect},
+    input, parse_field, OverflowError, ParseError, ReadError, Solution, SolveError,
+};
+
+/// The page ordering rules, keyed by the page that must come later.
+type Rules = HashMap<u32, HashSet<u32>>;
+
+/// The page ordering rules and the updates to print.
+type PrintQueue = (Rules, Vec<Vec<u32>>);
+
+pub struct Day5;
+
+impl Solution for Day5 {
+    const DAY: u8 = 5;
+
+    type Input<'a> = PrintQueue;
+    type Output1 = u64;
+    type Output2 = u64;
+
+    fn parse(input: &str) -> Result<Self::Input<'_>, ParseError> {
+        parse(input)
+    }
+
+    fn part1(input: &Self::Input<'_>) -> Result<u64, SolveError> {
+        Ok(part1_impl(input)?)
+    }
+
+    fn part2(input: &Self::Input<'_>) -> Result<u64, SolveError> {
+        Ok(part2_impl(input)?)
+    }
+}
+
+/// Checks the input against a grammar, rejecting anything out of place.
+pub struct Day5Strict;
+
+impl Solution for Day5Strict {
+    const DAY: u8 = 5;
+    const VARIANT: &'static str = "strict";
+
+    type Input<'a> = PrintQueue;
+    type Output1 = u64;
+    type Output2 = u64;
+
+    fn parse(input: &str) -> Result<Self::Input<'_>, ParseError> {
+        parse_strict(input)
+    }
+
+    fn part1(input: &Self::Input<'_>) -> Result<u64, SolveError> {
+        Ok(part1_impl(input)?)
+    }
 
-use crate::parse_number;
+    fn part2(input: &Self::Input<'_>) -> Result<u64, SolveError> {
+        Ok(part2_impl(input)?)
+    }
+}
 
 #[aoc_generator(day5)]
-fn parse(input: &str) -> (HashMap<u32, HashSet<u32>>, Vec<Vec<u32>>) {
-    let (s1, s2) = input.split_once("\n\n").unwrap();
+fn parse(input: &str) -> Result<PrintQueue, ParseError> {
+    let mut lines = input::lines(input).zip(1..);
+    let mut rules = Rules::default();
+    let mut last = (0, 0);
 
-    let mut rules = HashMap::<u32, HashSet<u32>>::default();
+    // The rules run up to the first blank line, and the updates follow it.
+    loop {
+        let Some((l, n)) = lines.next() else {
+            return Err(missing_blank_line(last));
+        };
+        if l.is_empty() {
+            break;
+        }
+        read_rule(n, l, &mut rules)?;
+        last = (l.len(), n);
+    }
 
-    for l in s1.lines() {
-        let (x, y) = l.split_once('|').unwrap();
-        rules
-            .entry(parse_number(y))
-            .or_default()
-            .insert(parse_number(x));
+    let pages = lines
+        .map(|(l, n)| read_update(n, l))
+        .collect::<Result<_, _>>()?;
+
+    Ok((rules, pages))
+}
+
+/// Reports running out of input before the blank line after the rules, just after the last line
+/// we read, given its length and line number.
+fn missing_blank_line((len, n): (usize, usize)) -> ParseError {
+    ParseError::new(
+        5,
+        n.max(1),
+        len + 1,
+        "",
+        "a blank line between the rules and the updates",
+    )
+}
+
+/// Adds the rule on line number `n` of the input to `rules`.
+fn read_rule(n: usize, l: &str, rules: &mut Rules) -> Result<(), ParseError> {
+    let (x, y) = l
+        .split_once('|')
+        .ok_or_else(|| ParseError::new(5, n, 1, l, "a rule like 47|53"))?;
+    let page = |p| parse_field(5, n, column_of(l, p), p, "a page number");
+    rules.entry(page(y)?).or_default().insert(page(x)?);
+    Ok(())
+}
+
+/// Reads the pages of the update on line number `n` of the input.
+fn read_update(n: usize, l: &str) -> Result<Vec<u32>, ParseError> {
+    l.split(',')
+        .map(|p| parse_field(5, n, column_of(l, p), p, "a page number"))
+        .collect()
+}
+
+/// Parses a page ordering rule, like `47|53`, into the page that comes first and the one after.
+fn rule(input: &mut &str) -> PResult<(u32, u32)> {
+    (
+        dec_uint.context(expect("a page number")),
+        '|'.context(expect("a rule like 47|53")),
+        dec_uint.context(expect("a page number")),
+    )
+        .map(|(x, _, y)| (x, y))
+        .parse_next(input)
+}
+
+/// Parses a line of the rules. Running out of input here means the updates are missing.
+fn rule_line(input: &mut &str) -> PResult<(u32, u32)> {
+    preceded(
+        not(eof).context(expect("a blank line between the rules and the updates")),
+        cut_err(terminated(rule, grammar::end_of_line)),
+    )
+    .parse_next(input)
+}
+
+/// Parses an update: an odd number of pages, separated by commas, so there's one in the middle.
+fn update(input: &mut &str) -> PResult<Vec<u32>> {
+    separated(1.., cut_err(dec_uint::<_, u32, _>.context(expect("a page number"))), ',')
+        .verify(|pages: &Vec<u32>| pages.len() % 2 == 1)
+        .context(expect("an odd number of pages"))
+        .parse_next(input)
+}
+
+/// Parses the input with a grammar, which unlike [`parse`] also rejects extra whitespace and
+/// updates without a middle page.
+fn parse_strict(input: &str) -> Result<PrintQueue, ParseError> {
+    let rules = repeat_till(0.., rule_line, line_ending).map(|(rules, _): (Vec<_>, _)| rules);
+    let (pairs, pages) = grammar::parse(5, input, (rules, grammar::lines(update)))?;
+
+    let mut rules = Rules::default();
+    for (x, y) in pairs {
+        rules.entry(y).or_default().insert(x);
+    }
+
+    Ok((rules, pages))
+}
+
+#[aoc_generator(day5, part1, strict)]
+fn parse_strict_part1(input: &str) -> Result<PrintQueue, ParseError> {
+    parse_strict(input)
+}
+
+#[aoc_generator(day5, part2, strict)]
+fn parse_strict_part2(input: &str) -> Result<PrintQueue, ParseError> {
+    parse_strict(input)
+}
+
+#[aoc(day5, part1, strict)]
+fn part1_strict(input: &PrintQueue) -> Result<u64, OverflowError> {
+    part1_impl(input)
+}
+
+#[aoc(day5, part2, strict)]
+fn part2_strict(input: &PrintQueue) -> Result<u64, OverflowError> {
+    part2_impl(input)
+}
+
+/// Reads the rules from `input`, then works through the updates one line at a time, adding up
+/// what `middle` makes of each.
+fn sum_updates(
+    mut input: impl BufRead,
+    mut middle: impl FnMut(&Rules, &mut [u32]) -> Option<u32>,
+) -> Result<u64, ReadError> {
+    let mut rules = Rules::default();
+    let mut line = String::new();
+    let mut n = 0;
+
+    loop {
+        let last = (input::trim_line(&line).len(), n);
+        line.clear();
+        if input.read_line(&mut line)? == 0 {
+            return Err(missing_blank_line(last).into());
+        }
+        n += 1;
+
+        let l = input::trim_line(&line);
+        if l.is_empty() {
+            break;
+        }
+        read_rule(n, l, &mut rules)?;
     }
 
-    let pages = s2
-        .lines()
-        .map(|l| l.split(',').map(parse_number).collect::<Vec<_>>())
-        .collect();
+    let mut sum = 0;
+    // Blank lines are only allowed at the end, so hold on to the first of a run of them until we
+    // know whether anything follows.
+    let mut blank = None;
+
+    loop {
+        line.clear();
+        if input.read_line(&mut line)? == 0 {
+            return Ok(sum);
+        }
+        n += 1;
 
-    (rules, pages)
+        let l = input::trim_line(&line);
+        if l.is_empty() {
+            blank = blank.or(Some(n));
+            continue;
+        }
+        if let Some(blank) = blank {
+            return Err(ParseError::new(5, blank, 1, "", "a page number").into());
+        }
+
+        let mut update = read_update(n, l)?;
+        sum = SUM.add(sum, middle(&rules, &mut update).unwrap_or(0) as u64)?;
+    }
 }
 
+const SUM: Answer = Answer::new(5, "the sum of the middle pages");
+
 #[aoc(day5, part1)]
-fn part1_impl(input: &(HashMap<u32, HashSet<u32>>, Vec<Vec<u32>>)) -> u32 {
+fn part1_impl(input: &PrintQueue) -> Result<u64, OverflowError> {
     let (rules, pages) = input;
 
-    pages
-        .iter()
-        .filter(|page| {
-            page.is_sorted_by(|a, b| rules.get(b).map_or_else(|| false, |set| set.contains(a)))
-        })
-        .map(|page| page[page.len() / 2])
-        .sum()
+    SUM.sum(
+        pages
+            .iter()
+            .filter(|page| in_order(rules, page))
+            .map(|page| page[page.len() / 2] as u64),
+    )
 }
 
 #[aoc(day5, part2)]
-fn part2_impl(input: &(HashMap<u32, HashSet<u32>>, Vec<Vec<u32>>)) -> u32 {
+fn part2_impl(input: &PrintQueue) -> Result<u64, OverflowError> {
     let (rules, pages) = input;
 
     let mut pages = pages.clone();
 
-    pages
-        .iter_mut()
-        .filter(|page| {
-            !page.is_sorted_by(|a, b| rules.get(b).map_or_else(|| false, |set| set.contains(a)))
-        })
-        .map(|page| {
-            page.sort_by(|a, b| {
-                rules
-                    .get(b)
-                    .map_or_else(|| false, |set| set.contains(a))
-                    .cmp(&true)
-            });
-            page[page.len() / 2]
-        })
-        .sum()
+    SUM.sum(
+        pages
+            .iter_mut()
+            .filter(|page| !in_order(rules, page))
+            .map(|page| reorder(rules, page) as u64),
+    )
 }
 
-pub fn part1(input: &str) -> u32 {
-    part1_impl(&parse(input))
+fn in_order(rules: &Rules, page: &[u32]) -> bool {
+    page.is_sorted_by(|a, b| rules.get(b).map_or_else(|| false, |set| set.contains(a)))
 }
 
-pub fn part2(input: &str) -> u32 {
-    part2_impl(&parse(input))
+/// Sorts the pages of an update by the rules, returning the one in the middle.
+fn reorder(rules: &Rules, page: &mut [u32]) -> u32 {
+    page.sort_by(|a, b| {
+        rules
+            .get(b)
+            .map_or_else(|| false, |set| set.contains(a))
+            .cmp(&true)
+    });
+    page[page.len() / 2]
+}
+
+pub fn part1(input: &str) -> Result<u64, SolveError> {
+    Ok(part1_impl(&parse(input)?)?)
+}
+
+pub fn part2(input: &str) -> Result<u64, SolveError> {
+    Ok(part2_impl(&parse(input)?)?)
+}
+
+/// Checks that the input has rules, a blank line and then updates, that every update has a middle
+/// page, and that the rules cover every page in them, returning every problem rather than
+/// stopping at the first like [`parse`].
+pub fn validate(input: &str) -> Vec<ParseError> {
+    let mut errors = Vec::new();
+    let mut lines = input::lines(input).zip(1..);
+    let mut covered = HashSet::default();
+    let mut last = (0, 0);
+
+    let blank = loop {
+        let Some((l, n)) = lines.next() else {
+            break None;
+        };
+        if l.is_empty() {
+            break Some(n);
+        }
+        last = (l.len(), n);
+
+        let Some((x, y)) = l.split_once('|') else {
+            errors.push(ParseError::new(5, n, 1, l, "a rule like 47|53"));
+            continue;
+        };
+        for p in [x, y] {
+            match parse_field(5, n, column_of(l, p), p, "a page number") {
+                Ok(page) => {
+                    covered.insert(page);
+                }
+                Err(e) => errors.push(e),
+            }
+        }
+    };
+
+    let Some(blank) = blank else {
+        errors.push(missing_blank_line(last));
+        return errors;
+    };
+    if blank == 1 {
+        errors.push(ParseError::new(5, 1, 1, "", "a rule like 47|53"));
+    }
+
+    // Blank lines at the end are dropped, so there's always an update after the blank line.
+    for (l, n) in lines {
+        if l.split(',').count().is_multiple_of(2) {
+            errors.push(ParseError::new(5, n, 1, l, "an odd number of pages"));
+        }
+        for p in l.split(',') {
+            let column = column_of(l, p);
+            match parse_field(5, n, column, p, "a page number") {
+                Ok(page) if !covered.contains(&page) => errors.push(ParseError::new(
+                    5,
+                    n,
+                    column,
+                    p,
+                    "a page covered by the rules",
+                )),
+                Ok(_) => {}
+                Err(e) => errors.push(e),
+            }
+        }
+    }
+
+    errors
+}
+
+/// Like [`part1`], but reads the updates a line at a time, so there can be any number of them.
+pub fn part1_reader(input: impl BufRead) -> Result<u64, ReadError> {
+    sum_updates(input, |rules, page| {
+        in_order(rules, page).then(|| page[page.len() / 2])
+    })
+}
+
+/// Like [`part2`], but reads the updates a line at a time, so there can be any number of them.
+pub fn part2_reader(input: impl BufRead) -> Result<u64, ReadError> {
+    sum_updates(input, |rules, page| {
+        (!in_order(rules, page)).then(|| reorder(rules, page))
+    })
 }
 
 #[cfg(test)]
@@ -105,11 +399,134 @@ mod tests {
 
     #[test]
     fn part1_example() {
-        assert_eq!(part1(INPUT), 143);
+        assert_eq!(part1(INPUT), Ok(143));
     }
 
     #[test]
     fn part2_example() {
-        assert_eq!(part2(INPUT), 123);
+        assert_eq!(part2(INPUT), Ok(123));
+    }
+
+    #[test]
+    fn reports_malformed_input() {
+        assert_eq!(
+            parse("47|53\n97|13"),
+            Err(ParseError::new(
+                5,
+                2,
+                6,
+                "",
+                "a blank line between the rules and the updates"
+            ))
+        );
+        assert_eq!(
+            parse("47|53\n97-13\n\n47,53,97"),
+            Err(ParseError::new(5, 2, 1, "97-13", "a rule like 47|53"))
+        );
+        assert_eq!(
+            parse("47|53\n97|x\n\n47,53,97"),
+            Err(ParseError::new(5, 2, 4, "x", "a page number"))
+        );
+        assert_eq!(
+            parse("47|53\n\n47,53,97\n47,,97"),
+            Err(ParseError::new(5, 4, 4, "", "a page number"))
+        );
+        assert!(matches!(
+            part1_reader("47|53\n97|13".as_bytes()),
+            Err(ReadError::Parse(e)) if e == ParseError::new(
+                5,
+                2,
+                6,
+                "",
+                "a blank line between the rules and the updates"
+            )
+        ));
+        assert!(matches!(
+            part2_reader("47|53\n\n47,53,97\n47,,97\n".as_bytes()),
+            Err(ReadError::Parse(e)) if e == ParseError::new(5, 4, 4, "", "a page number")
+        ));
+        assert_eq!(
+            parse("100000|53\n\n53,100000").map(|(_, pages)| pages),
+            Ok(vec![vec![53, 100000]])
+        );
+    }
+
+    #[test]
+    fn handles_line_endings() {
+        for input in crate::input::variants(INPUT) {
+            assert_eq!(part1(&input), Ok(143), "{input:?}");
+            assert_eq!(part2(&input), Ok(123), "{input:?}");
+            assert_eq!(part1_reader(input.as_bytes()).unwrap(), 143, "{input:?}");
+            assert_eq!(part2_reader(input.as_bytes()).unwrap(), 123, "{input:?}");
+        }
+
+        let blank_in_updates = "47|53\n\n47,53,97\n\n53,97,47";
+        let err = ParseError::new(5, 4, 1, "", "a page number");
+        assert_eq!(parse(blank_in_updates).err(), Some(err.clone()));
+        assert!(matches!(
+            part1_reader(blank_in_updates.as_bytes()),
+            Err(ReadError::Parse(e)) if e == err
+        ));
+    }
+
+    #[test]
+    fn strict_parser_checks_everything() {
+        assert_eq!(parse_strict(INPUT), parse(INPUT));
+        let crlf = INPUT.replace('\n', "\r\n") + "\r\n";
+        assert_eq!(parse_strict(&crlf), parse(INPUT));
+
+        let blank = "a blank line between the rules and the updates";
+        for (input, err) in [
+            ("47|53\n97|13", ParseError::new(5, 2, 6, "", blank)),
+            ("47|53\n97|13\n", ParseError::new(5, 3, 1, "", blank)),
+            ("47|53\n97-13\n\n47", ParseError::new(5, 2, 3, "-13", "a rule like 47|53")),
+            ("47|53\n97|x\n\n47", ParseError::new(5, 2, 4, "x", "a page number")),
+            ("47|53 \n\n47", ParseError::new(5, 1, 6, "", "the end of the line")),
+            ("47|53\n\n47,,97", ParseError::new(5, 3, 4, ",97", "a page number")),
+            ("47|53\n\n47,53", ParseError::new(5, 3, 1, "47,53", "an odd number of pages")),
+            ("47|53\n\n47\n\n53", ParseError::new(5, 4, 1, "", "a page number")),
+            ("47|53\n\n47 ", ParseError::new(5, 3, 3, "", "the end of the line")),
+        ] {
+            assert_eq!(parse_strict(input).err(), Some(err), "{input:?}");
+        }
+    }
+
+    #[test]
+    fn validates_both_sections() {
+        assert_eq!(validate(INPUT), []);
+        assert_eq!(
+            validate("47|53\n97-13\n53|x\n\n47,53\n47,53,61\n\n53"),
+            [
+                ParseError::new(5, 2, 1, "97-13", "a rule like 47|53"),
+                ParseError::new(5, 3, 4, "x", "a page number"),
+                ParseError::new(5, 5, 1, "47,53", "an odd number of pages"),
+                ParseError::new(5, 6, 7, "61", "a page covered by the rules"),
+                ParseError::new(5, 7, 1, "", "a page number"),
+            ]
+        );
+        assert_eq!(
+            validate("47|53\n97|13"),
+            [ParseError::new(
+                5,
+                2,
+                6,
+                "",
+                "a blank line between the rules and the updates"
+            )]
+        );
+        assert_eq!(
+            validate("\n47"),
+            [
+                ParseError::new(5, 1, 1, "", "a rule like 47|53"),
+                ParseError::new(5, 2, 1, "47", "a page covered by the rules"),
+            ]
+        );
+    }
+
+    #[test]
+    fn reads_updates_a_line_at_a_time() {
+        let reader = || std::io::BufReader::with_capacity(4, INPUT.as_bytes());
+        assert_eq!(part1_reader(reader()).unwrap(), 143);
+        assert_eq!(part2_reader(reader()).unwrap(), 123);
     }
 }
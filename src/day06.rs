@@ -1,117 +1,149 @@
 use aoc_runner_derive::{aoc, aoc_generator};
-use itertools::Itertools;
+
+use crate::{
+    grid::Grid,
+    input,
+    point::{Heading, Point},
+    ParseError, Solution, SolveError,
+};
+
+/// The map of the lab, and where the guard starts from.
+type Lab = (Grid<u8>, Point);
+
+pub struct Day6;
+
+impl Solution for Day6 {
+    const DAY: u8 = 6;
+
+    type Input<'a> = Lab;
+    type Output1 = u64;
+    type Output2 = u64;
+
+    fn parse(input: &str) -> Result<Self::Input<'_>, ParseError> {
+        parse(input)
+    }
+
+    fn part1(input: &Self::Input<'_>) -> Result<u64, SolveError> {
+        Ok(part1_impl(input))
+    }
+
+    fn part2(input: &Self::Input<'_>) -> Result<u64, SolveError> {
+        Ok(part2_impl(input))
+    }
+}
 
 #[aoc_generator(day6)]
-fn parse(input: &str) -> (Vec<Vec<u8>>, (usize, usize)) {
+fn parse(input: &str) -> Result<Lab, ParseError> {
     // my puzzle input is 130*130, presumably to prevent people from using u128 as a compact representation of each line.
     // you only need to represent the grid with obstacle locations and highlight the starting point.
-    let mut start = (0, 0);
-
-    // bool and u8 both are size 1, align 0x1, so no real benefit to converting to a Vec of Vec of bools though.
-    let mut found_start = false;
-
-    (
-        input
-            .as_bytes()
-            .split(|b| *b == b'\n')
-            .enumerate()
-            .map(|(r, l)| {
-                if found_start {
-                    l.to_vec()
-                } else {
-                    let mut res = Vec::with_capacity(l.len());
-
-                    for (c, cell) in l.iter().enumerate() {
-                        match cell {
-                            b'^' => {
-                                start = (r, c);
-                                found_start = true;
-                            }
-                            _ => {}
-                        }
-                        res.push(*cell)
-                    }
-                    res
-                }
-            })
-            .collect::<Vec<_>>(),
-        start,
-    )
+    // bool and u8 both are size 1, align 0x1, so no real benefit to converting to a grid of bools though.
+    let map = Grid::parse(6, input)?;
+
+    // Without a guard, point at the end of the map.
+    let start = map.position(|cell| *cell == b'^').ok_or_else(|| {
+        ParseError::new(
+            6,
+            map.height(),
+            map.width() + 1,
+            "",
+            "a guard facing up, marked by '^'",
+        )
+    })?;
+
+    Ok((map, start))
 }
 
 #[aoc(day6, part1)]
-fn part1_impl(input: &(Vec<Vec<u8>>, (usize, usize))) -> usize {
+fn part1_impl(input: &Lab) -> u64 {
     let (map, start) = input;
 
-    patrol(&map, start.0, start.1, true).unwrap().len()
+    patrol(map, *start, true).unwrap().len() as u64
 }
 
 #[aoc(day6, part2)]
-fn part2_impl(input: &(Vec<Vec<u8>>, (usize, usize))) -> usize {
+fn part2_impl(input: &Lab) -> u64 {
     let (map, start) = input;
 
     // FIXME: this seems a bit rubbish?
     let mut map = map.clone();
 
-    let path = patrol(&map, start.0, start.1, true).unwrap();
+    let path = patrol(&map, *start, true).unwrap();
 
     // Walk the path from part 1, turning every cell on the path into an obstacle and seeing if we
     // get a loop.
     // This is a lazy brute force approach. I thought I would be able to optimise it by looking at
     // potential corners, but not got that working yet.
     path.iter()
-        .filter(|&&(r, c)| {
-            map[r][c] = b'#';
-            let ok = patrol(&map, start.0, start.1, false).is_none();
-            map[r][c] = b'.';
+        .filter(|&&pos| {
+            map[pos] = b'#';
+            let ok = patrol(&map, *start, false).is_none();
+            map[pos] = b'.';
             ok
         })
-        .count()
+        .count() as u64
 }
 
-fn patrol(
-    m: &[Vec<u8>],
-    mut r: usize,
-    mut c: usize,
-    return_squares: bool,
-) -> Option<Vec<(usize, usize)>> {
-    let mut seen = vec![vec![[false; 4]; m[0].len()]; m.len()];
-    let mut d = 0;
+fn patrol(m: &Grid<u8>, mut pos: Point, return_squares: bool) -> Option<Vec<Point>> {
+    let mut seen = Grid::new(m.width(), m.height(), [false; 4]);
+    let mut heading = Heading::Up;
 
     loop {
-        if seen[r][c][d] {
+        if seen[pos][heading.index()] {
             return None;
         }
 
-        seen[r][c][d] = true;
-        let (dr, dc) = [(-1, 0), (0, 1), (1, 0), (0, -1)][d];
-        let (rr, cc) = (r.wrapping_add(dr as usize), c.wrapping_add(dc as usize));
+        seen[pos][heading.index()] = true;
 
-        if !(0..m.len()).contains(&rr) || !(0..m[0].len()).contains(&cc) {
+        let Some(next) = m.step(pos, heading) else {
             if !return_squares {
                 return Some(Vec::new());
             }
-            let visited = (0..m.len())
-                .cartesian_product(0..m[0].len())
-                .filter(|&(r, c)| seen[r][c].iter().any(|&b| b))
+            let visited = seen
+                .iter()
+                .filter(|(_, dirs)| dirs.iter().any(|&b| b))
+                .map(|(pos, _)| pos)
                 .collect();
             return Some(visited);
-        }
+        };
 
-        if m[rr][cc] == b'#' {
-            d = (d + 1) % 4;
+        if m[next] == b'#' {
+            heading = heading.turn_right();
         } else {
-            (r, c) = (rr, cc);
+            pos = next;
         }
     }
 }
 
-pub fn part1(input: &str) -> usize {
-    part1_impl(&parse(input))
+pub fn part1(input: &str) -> Result<u64, SolveError> {
+    Ok(part1_impl(&parse(input)?))
 }
 
-pub fn part2(input: &str) -> usize {
-    part2_impl(&parse(input))
+pub fn part2(input: &str) -> Result<u64, SolveError> {
+    Ok(part2_impl(&parse(input)?))
+}
+
+/// Checks that the input is a rectangular map of open floor and obstacles with exactly one guard,
+/// returning every problem rather than stopping at the first like [`parse`].
+pub fn validate(input: &str) -> Vec<ParseError> {
+    let mut errors = Grid::validate(6, input, |b| b".#^".contains(&b), "'.', '#' or '^'");
+
+    let mut guards = input::lines(input).enumerate().flat_map(|(n, line)| {
+        line.match_indices('^').map(move |(c, _)| (n + 1, c + 1))
+    });
+    // Without a guard, point at the end of the map, if there is one.
+    let last = input::lines(input).enumerate().last();
+    if let (None, Some((n, line))) = (guards.next(), last) {
+        errors.push(ParseError::new(
+            6,
+            n + 1,
+            line.len() + 1,
+            "",
+            "a guard facing up, marked by '^'",
+        ));
+    }
+    errors.extend(guards.map(|(n, c)| ParseError::new(6, n, c, "^", "only one guard")));
+
+    errors
 }
 
 #[cfg(test)]
@@ -131,11 +163,56 @@ mod tests {
 
     #[test]
     fn part1_example() {
-        assert_eq!(part1(&INPUT), 41);
+        assert_eq!(part1(INPUT), Ok(41));
     }
 
     #[test]
     fn part2_example() {
-        assert_eq!(part2(&INPUT), 6);
+        assert_eq!(part2(INPUT), Ok(6));
+    }
+
+    #[test]
+    fn handles_line_endings() {
+        for input in crate::input::variants(INPUT) {
+            assert_eq!(part1(&input), Ok(41), "{input:?}");
+            assert_eq!(part2(&input), Ok(6), "{input:?}");
+        }
+    }
+
+    #[test]
+    fn validates_the_map() {
+        assert_eq!(validate(INPUT), []);
+        assert_eq!(
+            validate("..#\n.^^\n.>.\n^."),
+            [
+                ParseError::new(6, 3, 2, ">", "'.', '#' or '^'"),
+                ParseError::new(6, 4, 1, "^.", "a row as long as the first"),
+                ParseError::new(6, 2, 3, "^", "only one guard"),
+                ParseError::new(6, 4, 1, "^", "only one guard"),
+            ]
+        );
+        assert_eq!(
+            validate("..#\n..."),
+            [ParseError::new(6, 2, 4, "", "a guard facing up, marked by '^'")]
+        );
+        assert_eq!(validate(""), [ParseError::new(6, 1, 1, "", "a row of cells")]);
+    }
+
+    #[test]
+    fn reports_malformed_maps() {
+        assert_eq!(
+            parse("..#\n...\n..."),
+            Err(ParseError::new(
+                6,
+                3,
+                4,
+                "",
+                "a guard facing up, marked by '^'"
+            ))
+        );
+        assert_eq!(
+            parse("..#\n.^.\n.."),
+            Err(ParseError::new(6, 3, 1, "..", "a row as long as the first"))
+        );
     }
 }
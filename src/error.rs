@@ -0,0 +1,220 @@
+use std::{error::Error, fmt, io};
+
+/// Malformed puzzle input, pinned to where we found it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    pub day: u8,
+    /// 1-based line of the input.
+    pub line: usize,
+    /// 1-based column within the line, counted in bytes.
+    pub column: usize,
+    /// The text we couldn't make sense of.
+    pub text: String,
+    /// What we wanted to find there instead.
+    pub expected: &'static str,
+}
+
+impl ParseError {
+    pub fn new(
+        day: u8,
+        line: usize,
+        column: usize,
+        text: impl Into<String>,
+        expected: &'static str,
+    ) -> Self {
+        ParseError {
+            day,
+            line,
+            column,
+            text: text.into(),
+            expected,
+        }
+    }
+
+    /// Builds an error for the `len` bytes at `offset` in the whole input, working out the line
+    /// and column. Handy for parsers that don't go line by line.
+    pub fn at(day: u8, input: &[u8], offset: usize, len: usize, expected: &'static str) -> Self {
+        let offset = offset.min(input.len());
+        let line_start = input[..offset]
+            .iter()
+            .rposition(|b| *b == b'\n')
+            .map_or(0, |i| i + 1);
+        let line = input[..line_start].iter().filter(|b| **b == b'\n').count() + 1;
+        let end = (offset + len).min(input.len());
+
+        ParseError::new(
+            day,
+            line,
+            offset - line_start + 1,
+            String::from_utf8_lossy(&input[offset..end]),
+            expected,
+        )
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "day {} input, line {}, column {}: expected {}, found '{}'",
+            self.day, self.line, self.column, self.expected, self.text
+        )
+    }
+}
+
+impl Error for ParseError {}
+
+/// An answer too big for a `u64`, caught when built with the `checked` feature.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OverflowError {
+    pub day: u8,
+    /// The answer that overflowed, like "the total distance".
+    pub what: &'static str,
+}
+
+impl fmt::Display for OverflowError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "day {}: {} is too big for a u64", self.day, self.what)
+    }
+}
+
+impl Error for OverflowError {}
+
+/// A location ID too big for an index that holds every ID up to the largest it takes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct IdTooBigError {
+    pub id: u32,
+    /// The largest ID the index takes.
+    pub max: u32,
+}
+
+impl fmt::Display for IdTooBigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "location ID {} is bigger than {}", self.id, self.max)
+    }
+}
+
+impl Error for IdTooBigError {}
+
+/// Something stopping a part from being solved: either malformed input, or an answer that
+/// overflowed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SolveError {
+    Parse(ParseError),
+    Overflow(OverflowError),
+}
+
+impl fmt::Display for SolveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SolveError::Parse(e) => e.fmt(f),
+            SolveError::Overflow(e) => e.fmt(f),
+        }
+    }
+}
+
+impl Error for SolveError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            SolveError::Parse(e) => Some(e),
+            SolveError::Overflow(e) => Some(e),
+        }
+    }
+}
+
+impl From<ParseError> for SolveError {
+    fn from(e: ParseError) -> Self {
+        SolveError::Parse(e)
+    }
+}
+
+impl From<OverflowError> for SolveError {
+    fn from(e: OverflowError) -> Self {
+        SolveError::Overflow(e)
+    }
+}
+
+/// Something going wrong while solving straight from a reader: either the reading itself, what
+/// we read, or the answer we worked out from it.
+#[derive(Debug)]
+pub enum ReadError {
+    Io(io::Error),
+    Parse(ParseError),
+    Overflow(OverflowError),
+}
+
+impl fmt::Display for ReadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReadError::Io(e) => write!(f, "reading input: {e}"),
+            ReadError::Parse(e) => e.fmt(f),
+            ReadError::Overflow(e) => e.fmt(f),
+        }
+    }
+}
+
+impl Error for ReadError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            ReadError::Io(e) => Some(e),
+            ReadError::Parse(e) => Some(e),
+            ReadError::Overflow(e) => Some(e),
+        }
+    }
+}
+
+impl From<io::Error> for ReadError {
+    fn from(e: io::Error) -> Self {
+        ReadError::Io(e)
+    }
+}
+
+impl From<ParseError> for ReadError {
+    fn from(e: ParseError) -> Self {
+        ReadError::Parse(e)
+    }
+}
+
+impl From<OverflowError> for ReadError {
+    fn from(e: OverflowError) -> Self {
+        ReadError::Overflow(e)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn locates_offsets() {
+        let input = b"ab\ncd\nef";
+        assert_eq!(
+            ParseError::at(1, input, 4, 1, "a letter"),
+            ParseError::new(1, 2, 2, "d", "a letter")
+        );
+        assert_eq!(
+            ParseError::at(1, input, 0, 2, "a letter"),
+            ParseError::new(1, 1, 1, "ab", "a letter")
+        );
+        assert_eq!(
+            ParseError::at(1, input, 8, 1, "more input"),
+            ParseError::new(1, 3, 3, "", "more input")
+        );
+    }
+
+    #[test]
+    fn describes_the_problem() {
+        assert_eq!(
+            ParseError::new(5, 3, 4, "9x", "a page number").to_string(),
+            "day 5 input, line 3, column 4: expected a page number, found '9x'"
+        );
+        assert_eq!(
+            SolveError::from(OverflowError {
+                day: 1,
+                what: "the total distance"
+            })
+            .to_string(),
+            "day 1: the total distance is too big for a u64"
+        );
+    }
+}
@@ -0,0 +1,105 @@
+//! Pieces shared by the strict parsers, which describe each day's input as a winnow grammar and
+//! reject anything that doesn't fit it, rather than skipping over it like the fast paths may.
+//!
+//! Lines may end in `\n` or `\r\n`, and blank lines are allowed at the end of the input, but
+//! nothing else is: no stray whitespace, and no numbers too big for their type.
+
+use winnow::{
+    ascii::line_ending,
+    combinator::{alt, cut_err, eof, repeat, repeat_till, terminated},
+    error::{ContextError, StrContext},
+    PResult, Parser,
+};
+
+use crate::ParseError;
+
+/// Labels what a parser is looking for. It becomes the `expected` of the [`ParseError`] when the
+/// parser fails.
+pub(crate) fn expect(what: &'static str) -> StrContext {
+    StrContext::Label(what)
+}
+
+/// Matches the end of a line, or of the input.
+pub(crate) fn end_of_line(input: &mut &str) -> PResult<()> {
+    alt((line_ending.void(), eof.void()))
+        .context(expect("the end of the line"))
+        .parse_next(input)
+}
+
+/// Matches the blank lines at the end of the input.
+fn end_of_input(input: &mut &str) -> PResult<()> {
+    (repeat::<_, _, (), _, _>(0.., line_ending), eof)
+        .void()
+        .parse_next(input)
+}
+
+/// Matches each of the remaining lines of the input with `line`, which shouldn't consume the end
+/// of the line itself. Once a line is under way, any failure is reported from there rather than
+/// backtracking.
+pub(crate) fn lines<'a, O>(
+    line: impl Parser<&'a str, O, ContextError>,
+) -> impl Parser<&'a str, Vec<O>, ContextError> {
+    repeat_till(0.., terminated(cut_err(line), cut_err(end_of_line)), end_of_input)
+        .map(|(lines, _)| lines)
+}
+
+/// Runs `grammar` over the whole of `input`, turning a failure into a [`ParseError`] for the
+/// line and column where it happened, expecting whatever the innermost label said.
+pub(crate) fn parse<'a, O>(
+    day: u8,
+    input: &'a str,
+    mut grammar: impl Parser<&'a str, O, ContextError>,
+) -> Result<O, ParseError> {
+    grammar.parse(input).map_err(|e| {
+        let expected = e
+            .inner()
+            .context()
+            .find_map(|c| match c {
+                StrContext::Label(label) => Some(*label),
+                _ => None,
+            })
+            .unwrap_or("valid input");
+
+        let rest = &input[e.offset()..];
+        let len = rest.find(|c: char| c.is_ascii_whitespace()).unwrap_or(rest.len());
+        ParseError::at(day, input.as_bytes(), e.offset(), len, expected)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use winnow::ascii::dec_uint;
+
+    use super::*;
+
+    fn numbers(input: &str) -> Result<Vec<u8>, ParseError> {
+        parse(9, input, lines(dec_uint.context(expect("a number"))))
+    }
+
+    #[test]
+    fn parses_lines() {
+        assert_eq!(numbers("1\n2\r\n3"), Ok(vec![1, 2, 3]));
+        assert_eq!(numbers("1\n2\n\n"), Ok(vec![1, 2]));
+        assert_eq!(numbers(""), Ok(vec![]));
+    }
+
+    #[test]
+    fn reports_where_lines_fail() {
+        assert_eq!(
+            numbers("1\nx2 y\n3"),
+            Err(ParseError::new(9, 2, 1, "x2", "a number"))
+        );
+        assert_eq!(
+            numbers("1\n2 \n3"),
+            Err(ParseError::new(9, 2, 2, "", "the end of the line"))
+        );
+        assert_eq!(
+            numbers("1\n256"),
+            Err(ParseError::new(9, 2, 1, "256", "a number"))
+        );
+        assert_eq!(
+            numbers("1\n\n3"),
+            Err(ParseError::new(9, 2, 1, "", "a number"))
+        );
+    }
+}
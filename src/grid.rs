@@ -0,0 +1,361 @@
+//! A rectangular grid of cells, as found in many of the puzzles, stored as a single flat `Vec`.
+
+use std::{
+    fmt,
+    ops::{Index, IndexMut},
+};
+
+use crate::{
+    input,
+    point::{Direction, Heading, Point},
+    ParseError,
+};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Grid<T> {
+    cells: Vec<T>,
+    width: usize,
+    height: usize,
+}
+
+impl<T> Grid<T> {
+    pub fn new(width: usize, height: usize, fill: T) -> Self
+    where
+        T: Clone,
+    {
+        Grid {
+            cells: vec![fill; width * height],
+            width,
+            height,
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn get(&self, pos: Point) -> Option<&T> {
+        if pos.row < self.height && pos.col < self.width {
+            Some(&self.cells[pos.row * self.width + pos.col])
+        } else {
+            None
+        }
+    }
+
+    pub fn get_mut(&mut self, pos: Point) -> Option<&mut T> {
+        if pos.row < self.height && pos.col < self.width {
+            Some(&mut self.cells[pos.row * self.width + pos.col])
+        } else {
+            None
+        }
+    }
+
+    /// Returns the position one step away from `pos` in `dir`, if that's still inside the grid.
+    pub fn step(&self, pos: Point, dir: impl Into<Direction>) -> Option<Point> {
+        pos.step(dir, self.width, self.height)
+    }
+
+    /// Returns the cell one step away from `pos` in `dir`, if that's still inside the grid.
+    pub fn neighbour(&self, pos: Point, dir: impl Into<Direction>) -> Option<&T> {
+        self.step(pos, dir).map(|p| &self[p])
+    }
+
+    /// Returns the positions orthogonally next to `pos` that are inside the grid.
+    pub fn neighbours4(&self, pos: Point) -> impl Iterator<Item = Point> + '_ {
+        Heading::ALL.into_iter().filter_map(move |h| self.step(pos, h))
+    }
+
+    /// Returns the positions surrounding `pos`, including diagonals, that are inside the grid.
+    pub fn neighbours8(&self, pos: Point) -> impl Iterator<Item = Point> + '_ {
+        Direction::ALL
+            .into_iter()
+            .filter_map(move |d| self.step(pos, d))
+    }
+
+    /// Walks from `start` (included) in `dir` until leaving the grid.
+    pub fn ray(&self, start: Point, dir: Direction) -> impl Iterator<Item = (Point, &T)> + '_ {
+        std::iter::successors(self.get(start).map(|_| start), move |pos| {
+            self.step(*pos, dir)
+        })
+        .map(|pos| (pos, &self[pos]))
+    }
+
+    pub fn row(&self, r: usize) -> &[T] {
+        &self.cells[r * self.width..(r + 1) * self.width]
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = &[T]> + '_ {
+        self.cells.chunks_exact(self.width.max(1))
+    }
+
+    pub fn column(&self, c: usize) -> impl Iterator<Item = &T> + '_ {
+        self.ray(Point::new(0, c), Direction::Down).map(|(_, v)| v)
+    }
+
+    pub fn columns(&self) -> impl Iterator<Item = impl Iterator<Item = &T> + '_> + '_ {
+        (0..self.width).map(|c| self.column(c))
+    }
+
+    /// Returns every line running down and to the right, starting from the bottom left corner.
+    pub fn diagonals(&self) -> impl Iterator<Item = impl Iterator<Item = &T> + '_> + '_ {
+        let starts = (0..self.height)
+            .rev()
+            .map(|r| Point::new(r, 0))
+            .chain((1..self.width).map(|c| Point::new(0, c)));
+        starts.map(|start| self.ray(start, Direction::DownRight).map(|(_, v)| v))
+    }
+
+    /// Returns every line running down and to the left, starting from the top left corner.
+    pub fn anti_diagonals(&self) -> impl Iterator<Item = impl Iterator<Item = &T> + '_> + '_ {
+        // Without any columns, there's no right-hand side to start lines down.
+        let rows = if self.width == 0 { 0..0 } else { 1..self.height };
+        let starts = (0..self.width)
+            .map(|c| Point::new(0, c))
+            .chain(rows.map(|r| Point::new(r, self.width - 1)));
+        starts.map(|start| self.ray(start, Direction::DownLeft).map(|(_, v)| v))
+    }
+
+    /// Returns every position in the grid, row by row.
+    pub fn positions(&self) -> impl Iterator<Item = Point> {
+        let width = self.width;
+        (0..self.cells.len()).map(move |i| Point::new(i / width, i % width))
+    }
+
+    /// Returns every cell in the grid along with its position, row by row.
+    pub fn iter(&self) -> impl Iterator<Item = (Point, &T)> + '_ {
+        self.positions().zip(&self.cells)
+    }
+
+    /// Returns the position of the first cell, row by row, matching `pred`.
+    pub fn position(&self, pred: impl Fn(&T) -> bool) -> Option<Point> {
+        self.cells
+            .iter()
+            .position(pred)
+            .map(|i| Point::new(i / self.width, i % self.width))
+    }
+}
+
+impl Grid<u8> {
+    /// Parses a grid with a row per line and a byte per cell. Every row must be the same length.
+    pub fn parse(day: u8, input: &str) -> Result<Self, ParseError> {
+        let mut lines = input::lines(input).map(str::as_bytes);
+        let first = lines.next().unwrap_or_default();
+        let width = first.len();
+
+        if width == 0 {
+            return Err(ParseError::new(day, 1, 1, "", "a row of cells"));
+        }
+
+        let mut cells = first.to_vec();
+        for (r, line) in lines.enumerate() {
+            if line.len() != width {
+                return Err(ParseError::new(
+                    day,
+                    r + 2,
+                    1,
+                    String::from_utf8_lossy(line),
+                    "a row as long as the first",
+                ));
+            }
+            cells.extend_from_slice(line);
+        }
+
+        Ok(Grid {
+            height: cells.len() / width,
+            cells,
+            width,
+        })
+    }
+
+    /// Checks that `input` is a grid [`Grid::parse`] would accept, with every cell one that
+    /// `is_cell` allows, reporting every problem rather than stopping at the first. Ragged rows
+    /// are reported as a whole, without looking at their cells.
+    pub fn validate(
+        day: u8,
+        input: &str,
+        is_cell: impl Fn(u8) -> bool,
+        cell: &'static str,
+    ) -> Vec<ParseError> {
+        let width = input::lines(input).next().map_or(0, str::len);
+        if width == 0 {
+            return vec![ParseError::new(day, 1, 1, "", "a row of cells")];
+        }
+
+        let mut errors = Vec::new();
+        for (n, line) in input::lines(input).map(str::as_bytes).enumerate() {
+            if line.len() != width {
+                errors.push(ParseError::new(
+                    day,
+                    n + 1,
+                    1,
+                    String::from_utf8_lossy(line),
+                    "a row as long as the first",
+                ));
+                continue;
+            }
+            for (c, b) in line.iter().enumerate() {
+                if !is_cell(*b) {
+                    let text = String::from_utf8_lossy(&line[c..=c]);
+                    errors.push(ParseError::new(day, n + 1, c + 1, text, cell));
+                }
+            }
+        }
+        errors
+    }
+}
+
+impl<T> Index<Point> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, pos: Point) -> &T {
+        assert!(pos.col < self.width, "column {} is outside the grid", pos.col);
+        &self.cells[pos.row * self.width + pos.col]
+    }
+}
+
+impl<T> IndexMut<Point> for Grid<T> {
+    fn index_mut(&mut self, pos: Point) -> &mut T {
+        assert!(pos.col < self.width, "column {} is outside the grid", pos.col);
+        &mut self.cells[pos.row * self.width + pos.col]
+    }
+}
+
+impl fmt::Display for Grid<u8> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (r, row) in self.rows().enumerate() {
+            if r > 0 {
+                writeln!(f)?;
+            }
+            f.write_str(&String::from_utf8_lossy(row))?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const fn p(row: usize, col: usize) -> Point {
+        Point::new(row, col)
+    }
+
+    fn grid() -> Grid<u8> {
+        Grid::parse(1, "abc\ndef").unwrap()
+    }
+
+    fn collect<'a>(lines: impl Iterator<Item = impl Iterator<Item = &'a u8>>) -> Vec<String> {
+        lines
+            .map(|l| l.map(|b| *b as char).collect::<String>())
+            .collect()
+    }
+
+    #[test]
+    fn parses_and_displays() {
+        let g = grid();
+        assert_eq!((g.width(), g.height()), (3, 2));
+        assert_eq!(g[p(1, 0)], b'd');
+        assert_eq!(g.to_string(), "abc\ndef");
+    }
+
+    #[test]
+    fn rejects_ragged_rows() {
+        assert_eq!(
+            Grid::parse(4, "abc\nde\nfgh"),
+            Err(ParseError::new(4, 2, 1, "de", "a row as long as the first"))
+        );
+        assert_eq!(
+            Grid::parse(4, ""),
+            Err(ParseError::new(4, 1, 1, "", "a row of cells"))
+        );
+    }
+
+    #[test]
+    fn validates_every_row() {
+        let letters = |input| Grid::validate(4, input, |b| b.is_ascii_lowercase(), "a letter");
+        assert_eq!(letters("abc\r\ndef\n"), []);
+        assert_eq!(
+            letters("abc\nd\nEfG\n\nghi"),
+            [
+                ParseError::new(4, 2, 1, "d", "a row as long as the first"),
+                ParseError::new(4, 3, 1, "E", "a letter"),
+                ParseError::new(4, 3, 3, "G", "a letter"),
+                ParseError::new(4, 4, 1, "", "a row as long as the first"),
+            ]
+        );
+        assert_eq!(letters(""), [ParseError::new(4, 1, 1, "", "a row of cells")]);
+    }
+
+    #[test]
+    fn checks_bounds() {
+        let mut g = grid();
+        assert_eq!(g.get(p(1, 2)), Some(&b'f'));
+        assert_eq!(g.get(p(2, 0)), None);
+        assert_eq!(g.get(p(0, 3)), None);
+        assert_eq!(g.step(p(0, 0), Heading::Up), None);
+        assert_eq!(g.step(p(0, 0), Direction::DownRight), Some(p(1, 1)));
+        assert_eq!(g.step(p(1, 2), Heading::Right), None);
+        assert_eq!(g.neighbour(p(1, 2), Heading::Up), Some(&b'c'));
+        assert_eq!(g.neighbour(p(1, 2), Direction::DownLeft), None);
+
+        *g.get_mut(p(0, 1)).unwrap() = b'B';
+        g[p(1, 1)] = b'E';
+        assert_eq!(g.to_string(), "aBc\ndEf");
+        assert!(g.get_mut(p(5, 5)).is_none());
+    }
+
+    #[test]
+    #[should_panic]
+    fn index_does_not_wrap_rows() {
+        let _ = grid()[p(0, 3)];
+    }
+
+    #[test]
+    fn finds_neighbours() {
+        let g = grid();
+        assert_eq!(
+            g.neighbours4(p(0, 1)).collect::<Vec<_>>(),
+            [p(0, 2), p(1, 1), p(0, 0)]
+        );
+        assert_eq!(
+            g.neighbours8(p(0, 1)).collect::<Vec<_>>(),
+            [p(0, 2), p(1, 2), p(1, 1), p(1, 0), p(0, 0)]
+        );
+    }
+
+    #[test]
+    fn walks_lines() {
+        let g = grid();
+        assert_eq!(g.rows().collect::<Vec<_>>(), [b"abc", b"def"]);
+        assert_eq!(g.row(1), b"def");
+        assert_eq!(collect(g.columns()), ["ad", "be", "cf"]);
+        assert_eq!(collect(g.diagonals()), ["d", "ae", "bf", "c"]);
+        assert_eq!(collect(g.anti_diagonals()), ["a", "bd", "ce", "f"]);
+        assert_eq!(
+            g.ray(p(1, 2), Direction::Left).collect::<Vec<_>>(),
+            [(p(1, 2), &b'f'), (p(1, 1), &b'e'), (p(1, 0), &b'd')]
+        );
+        assert_eq!(g.ray(p(2, 0), Direction::Right).count(), 0);
+
+        let empty = Grid::new(0, 3, b'.');
+        assert_eq!(empty.anti_diagonals().count(), 0);
+        assert_eq!(empty.diagonals().flatten().count(), 0);
+    }
+
+    #[test]
+    fn finds_cells() {
+        let g = grid();
+        assert_eq!(g.position(|b| *b == b'e'), Some(p(1, 1)));
+        assert_eq!(g.position(|b| *b == b'z'), None);
+        assert_eq!(g.positions().last(), Some(p(1, 2)));
+        assert_eq!(g.iter().nth(4), Some((p(1, 1), &b'e')));
+
+        let filled = Grid::new(2, 3, 0_u8);
+        assert_eq!((filled.width(), filled.height()), (2, 3));
+        assert!(filled.iter().all(|(_, v)| *v == 0));
+    }
+}
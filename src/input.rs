@@ -0,0 +1,71 @@
+//! Line handling shared by every day's parser, so inputs saved on Windows or tidied up by hand
+//! parse the same as the originals.
+//!
+//! Lines may end in `\n` or `\r\n`, have trailing whitespace, and be followed by blank lines at
+//! the end of the input. Everything returned borrows from the input, so errors can still point at
+//! the right column with [`crate::column_of`].
+
+/// Trims a line's ending, and any other whitespace after its last character.
+pub fn trim_line(line: &str) -> &str {
+    line.trim_ascii_end()
+}
+
+/// Returns the lines of `input`, trimmed as by [`trim_line`], leaving out any blank lines at the
+/// end. Blank lines elsewhere are kept, so counting lines still gives their line numbers.
+pub fn lines(input: &str) -> impl Iterator<Item = &str> {
+    let input = input.trim_ascii_end();
+    let mut lines = input.split('\n');
+    if input.is_empty() {
+        lines.next();
+    }
+    lines.map(trim_line)
+}
+
+/// Returns `input` with every combination of line endings and trailing whitespace we handle, for
+/// checking that each day gives the same answers whatever it's given.
+#[cfg(test)]
+pub fn variants(input: &str) -> Vec<String> {
+    let crlf = input.replace('\n', "\r\n");
+    let spaced = input.replace('\n', " \t\n");
+
+    vec![
+        format!("{input}\n"),
+        format!("{input}\n\n"),
+        format!("{crlf}\r\n"),
+        format!("{spaced}  "),
+        format!("{crlf}\r\n \r\n"),
+        crlf,
+        input.to_string(),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn trims_line_endings() {
+        assert_eq!(trim_line("1 2\r\n"), "1 2");
+        assert_eq!(trim_line("1 2 \t\n"), "1 2");
+        assert_eq!(trim_line("  1 2"), "  1 2");
+        assert_eq!(trim_line("\r\n"), "");
+    }
+
+    #[test]
+    fn splits_lines() {
+        for input in variants("ab\ncd\n\nef") {
+            assert_eq!(lines(&input).collect::<Vec<_>>(), ["ab", "cd", "", "ef"]);
+        }
+        assert_eq!(lines("").count(), 0);
+        assert_eq!(lines("\r\n\n  ").count(), 0);
+        assert_eq!(lines("\nab").collect::<Vec<_>>(), ["", "ab"]);
+    }
+
+    #[test]
+    fn keeps_positions_in_the_input() {
+        let input = "ab\r\ncd  \r\n";
+        let cd = lines(input).nth(1).unwrap();
+        assert_eq!(crate::column_of(&input[4..], cd), 1);
+        assert_eq!(cd.as_ptr(), input[4..].as_ptr());
+    }
+}
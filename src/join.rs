@@ -0,0 +1,187 @@
+//! Joins two sorted sequences by key, like a database merge join, without collecting either of
+//! them. Each key comes out once, with how many times it appeared on each side.
+
+use std::{cmp::Ordering, iter::Peekable};
+
+/// Which keys a [`MergeJoin`] yields.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Join {
+    /// Only keys on both sides.
+    Inner,
+    /// Every key on the left, whether or not it's on the right.
+    LeftOuter,
+    /// Every key on either side.
+    FullOuter,
+}
+
+/// Joins two sequences sorted by key, yielding each key along with the length of its run on the
+/// left and on the right, either of which may be 0 for an outer join.
+///
+/// The sequences must be sorted in ascending order. If they aren't, runs of the same key are
+/// split up and keys can be missed, though nothing worse happens.
+pub fn merge_join<L, R>(left: L, right: R, join: Join) -> MergeJoin<L::IntoIter, R::IntoIter>
+where
+    L: IntoIterator,
+    R: IntoIterator<Item = L::Item>,
+    L::Item: Ord,
+{
+    MergeJoin {
+        left: left.into_iter().peekable(),
+        right: right.into_iter().peekable(),
+        left_run: None,
+        right_run: None,
+        join,
+    }
+}
+
+/// An iterator over the runs of keys in two sorted sequences, made by [`merge_join`].
+pub struct MergeJoin<L: Iterator, R: Iterator> {
+    left: Peekable<L>,
+    right: Peekable<R>,
+    /// A run we've read but not yet yielded, as it was behind the other side.
+    left_run: Option<(L::Item, usize)>,
+    right_run: Option<(R::Item, usize)>,
+    join: Join,
+}
+
+/// Reads the next run of equal keys from `keys`, returning the key and how many times it came up.
+fn next_run<I: Iterator<Item: PartialEq>>(keys: &mut Peekable<I>) -> Option<(I::Item, usize)> {
+    let key = keys.next()?;
+    let mut len = 1;
+    while keys.next_if_eq(&key).is_some() {
+        len += 1;
+    }
+    Some((key, len))
+}
+
+impl<K, L, R> Iterator for MergeJoin<L, R>
+where
+    K: Ord,
+    L: Iterator<Item = K>,
+    R: Iterator<Item = K>,
+{
+    /// The key, and the length of its run on the left and on the right.
+    type Item = (K, usize, usize);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let left = self.left_run.take().or_else(|| next_run(&mut self.left));
+            let right = self.right_run.take().or_else(|| next_run(&mut self.right));
+
+            match (left, right) {
+                (Some((l, n)), Some((r, m))) => match l.cmp(&r) {
+                    Ordering::Less => {
+                        self.right_run = Some((r, m));
+                        if self.join != Join::Inner {
+                            return Some((l, n, 0));
+                        }
+                    }
+                    Ordering::Greater => {
+                        self.left_run = Some((l, n));
+                        if self.join == Join::FullOuter {
+                            return Some((r, 0, m));
+                        }
+                    }
+                    Ordering::Equal => return Some((l, n, m)),
+                },
+                (Some((l, n)), None) if self.join != Join::Inner => return Some((l, n, 0)),
+                (None, Some((r, m))) if self.join == Join::FullOuter => return Some((r, 0, m)),
+                _ => return None,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use proptest::prelude::*;
+
+    use super::*;
+
+    const LEFT: [u32; 6] = [1, 2, 3, 3, 3, 4];
+    const RIGHT: [u32; 6] = [3, 3, 3, 4, 5, 9];
+
+    fn join(left: &[u32], right: &[u32], join: Join) -> Vec<(u32, usize, usize)> {
+        merge_join(left.iter().copied(), right.iter().copied(), join).collect()
+    }
+
+    #[test]
+    fn joins_runs() {
+        assert_eq!(join(&LEFT, &RIGHT, Join::Inner), [(3, 3, 3), (4, 1, 1)]);
+        assert_eq!(
+            join(&LEFT, &RIGHT, Join::LeftOuter),
+            [(1, 1, 0), (2, 1, 0), (3, 3, 3), (4, 1, 1)]
+        );
+        assert_eq!(
+            join(&LEFT, &RIGHT, Join::FullOuter),
+            [
+                (1, 1, 0),
+                (2, 1, 0),
+                (3, 3, 3),
+                (4, 1, 1),
+                (5, 0, 1),
+                (9, 0, 1)
+            ]
+        );
+    }
+
+    #[test]
+    fn joins_empty_sides() {
+        assert_eq!(join(&[], &RIGHT, Join::Inner), []);
+        assert_eq!(join(&[], &RIGHT, Join::LeftOuter), []);
+        assert_eq!(join(&[], &RIGHT, Join::FullOuter).len(), 4);
+        assert_eq!(join(&LEFT, &[], Join::LeftOuter).len(), 4);
+        assert_eq!(join(&[], &[], Join::FullOuter), []);
+    }
+
+    #[test]
+    fn joins_any_ord_keys() {
+        let left = ["ab", "ab", "cd"];
+        let right = ["ab", "ef"];
+        assert_eq!(
+            merge_join(&left, &right, Join::FullOuter).collect::<Vec<_>>(),
+            [(&"ab", 2, 1), (&"cd", 1, 0), (&"ef", 0, 1)]
+        );
+    }
+
+    /// Counts each key on both sides, as a [`Join::FullOuter`] should.
+    fn count(left: &[u8], right: &[u8]) -> BTreeMap<u8, (usize, usize)> {
+        let mut counts = BTreeMap::<_, (usize, usize)>::new();
+        for k in left {
+            counts.entry(*k).or_default().0 += 1;
+        }
+        for k in right {
+            counts.entry(*k).or_default().1 += 1;
+        }
+        counts
+    }
+
+    proptest! {
+        #[test]
+        fn joins_like_counting(mut left: Vec<u8>, mut right: Vec<u8>) {
+            left.sort_unstable();
+            right.sort_unstable();
+            let counts = count(&left, &right);
+
+            for join in [Join::Inner, Join::LeftOuter, Join::FullOuter] {
+                let expected = counts
+                    .iter()
+                    .filter(|(_, (n, m))| match join {
+                        Join::Inner => *n > 0 && *m > 0,
+                        Join::LeftOuter => *n > 0,
+                        Join::FullOuter => true,
+                    })
+                    .map(|(k, (n, m))| (*k, *n, *m))
+                    .collect::<Vec<_>>();
+
+                prop_assert_eq!(
+                    merge_join(left.iter().copied(), right.iter().copied(), join)
+                        .collect::<Vec<_>>(),
+                    expected
+                );
+            }
+        }
+    }
+}
@@ -1,3 +1,19 @@
+use std::fmt::Display;
+use std::io::BufRead;
+use std::marker::PhantomData;
+
+pub mod answers;
+mod checked;
+mod error;
+mod grammar;
+pub mod grid;
+pub mod input;
+pub mod join;
+pub mod load;
+pub mod number;
+pub mod point;
+pub mod sort;
+
 #[path = "day01.rs"]
 pub mod day1;
 #[path = "day02.rs"]
@@ -11,17 +27,248 @@ pub mod day5;
 #[path = "day06.rs"]
 pub mod day6;
 
-// POWERS_OF_10 is used in tandem with parse_number to try to take advantage of the values in our problem.
-const POWERS_OF_10: [u32; 5] = [1, 10, 100, 1000, 10000];
+pub use error::{IdTooBigError, OverflowError, ParseError, ReadError, SolveError};
+
+/// A puzzle solution that can be driven from code, without going through cargo-aoc's generated
+/// runner.
+pub trait Solution {
+    /// The day of the puzzle, 1 to 25.
+    const DAY: u8;
+
+    /// Distinguishes alternate implementations of the same day, like the `naive` variants we
+    /// register with cargo-aoc.
+    const VARIANT: &'static str = "default";
+
+    /// The parsed form of the puzzle input. It may borrow from the raw input, and is shared
+    /// between threads when both parts are solved at once.
+    type Input<'a>: Send + Sync;
+    type Output1: Display;
+    type Output2: Display;
+
+    fn parse(input: &str) -> Result<Self::Input<'_>, ParseError>;
+
+    // Days that parse as they go, like day 2's inline path, report malformed input from the parts
+    // rather than from parse. With the `checked` feature, answers that overflow are reported too.
+    fn part1(input: &Self::Input<'_>) -> Result<Self::Output1, SolveError>;
+    fn part2(input: &Self::Input<'_>) -> Result<Self::Output2, SolveError>;
+}
+
+/// A [`Solution`] with its types erased, so that every day can sit in the same registry.
+pub trait Runner: Sync {
+    fn day(&self) -> u8;
+    fn variant(&self) -> &'static str;
+    fn parse<'a>(&self, input: &'a str) -> Result<Box<dyn Parsed + 'a>, ParseError>;
+}
+
+/// Puzzle input that has been through a [`Runner`]'s parser, ready to be solved.
+pub trait Parsed: Send + Sync {
+    fn part1(&self) -> Result<String, SolveError>;
+    fn part2(&self) -> Result<String, SolveError>;
+}
+
+struct Erased<S>(PhantomData<fn() -> S>);
+
+struct ParsedInput<'a, S: Solution>(S::Input<'a>);
+
+impl<S: Solution + 'static> Runner for Erased<S> {
+    fn day(&self) -> u8 {
+        S::DAY
+    }
+
+    fn variant(&self) -> &'static str {
+        S::VARIANT
+    }
+
+    fn parse<'a>(&self, input: &'a str) -> Result<Box<dyn Parsed + 'a>, ParseError> {
+        Ok(Box::new(ParsedInput::<S>(S::parse(input)?)))
+    }
+}
+
+impl<S: Solution> Parsed for ParsedInput<'_, S> {
+    fn part1(&self) -> Result<String, SolveError> {
+        S::part1(&self.0).map(|answer| answer.to_string())
+    }
+
+    fn part2(&self) -> Result<String, SolveError> {
+        S::part2(&self.0).map(|answer| answer.to_string())
+    }
+}
 
-fn parse_number(val: &str) -> u32 {
-    let radix = val.len() - 1;
+/// Every implemented day, in order. Where a day has alternate implementations, the one used by
+/// that day's public `part1` and `part2` functions comes first.
+static SOLUTIONS: &[&dyn Runner] = &[
+    &Erased::<day1::Day1>(PhantomData),
+    &Erased::<day1::Day1Swar>(PhantomData),
+    &Erased::<day1::Day1Strict>(PhantomData),
+    &Erased::<day1::Day1Radix>(PhantomData),
+    &Erased::<day1::Day1Hashed>(PhantomData),
+    &Erased::<day1::Day1Dense>(PhantomData),
+    &Erased::<day1::Day1Any>(PhantomData),
+    &Erased::<day2::Day2>(PhantomData),
+    &Erased::<day2::Day2Naive>(PhantomData),
+    &Erased::<day2::Day2Strict>(PhantomData),
+    &Erased::<day3::Day3>(PhantomData),
+    &Erased::<day4::Day4>(PhantomData),
+    &Erased::<day5::Day5>(PhantomData),
+    &Erased::<day5::Day5Strict>(PhantomData),
+    &Erased::<day6::Day6>(PhantomData),
+];
 
-    val.as_bytes()
+/// Returns every registered solution, including alternate variants.
+pub fn solutions() -> &'static [&'static dyn Runner] {
+    SOLUTIONS
+}
+
+/// Finds the solution for `day`, using the default implementation unless a `variant` is given.
+pub fn find(day: u8, variant: Option<&str>) -> Option<&'static dyn Runner> {
+    SOLUTIONS
         .iter()
-        .enumerate()
-        .map(|(n, c)| (c - 48) as u32 * POWERS_OF_10[radix - n])
-        .sum()
+        .copied()
+        .filter(|s| s.day() == day)
+        .find(|s| variant.is_none_or(|v| v == s.variant()))
+}
+
+/// Solves a part straight from a reader, for the days that can work through their input as it
+/// arrives without holding it all in memory. Returns `None` for the other days.
+pub fn solve_reader(
+    day: u8,
+    part: u8,
+    input: &mut dyn BufRead,
+) -> Option<Result<String, ReadError>> {
+    let answer = match (day, part) {
+        (2, 1) => day2::part1_reader(input),
+        (2, 2) => day2::part2_reader(input),
+        (3, 1) => day3::part1_reader(input),
+        (3, 2) => day3::part2_reader(input),
+        (5, 1) => day5::part1_reader(input),
+        (5, 2) => day5::part2_reader(input),
+        _ => return None,
+    };
+    Some(answer.map(|a| a.to_string()))
+}
+
+/// Checks the structure of a day's input before solving it, returning every problem found rather
+/// than just the first, as parsing would. Returns `None` for days without any checks.
+pub fn validate(day: u8, input: &str) -> Option<Vec<ParseError>> {
+    let errors = match day {
+        1 => day1::validate(input),
+        4 => day4::validate(input),
+        5 => day5::validate(input),
+        6 => day6::validate(input),
+        _ => return None,
+    };
+    Some(errors)
+}
+
+/// Parses `field` as a number, reporting anything that isn't one, or is too big for a `u32`, as
+/// malformed input. `line` and `column` locate the field for the error and are 1-based.
+fn parse_field(
+    day: u8,
+    line: usize,
+    column: usize,
+    field: &str,
+    expected: &'static str,
+) -> Result<u32, ParseError> {
+    number::parse_checked(field.as_bytes())
+        .map_err(|_| ParseError::new(day, line, column, field, expected))
+}
+
+/// Returns the 1-based column at which `field`, a subslice of `line`, starts.
+fn column_of(line: &(impl AsRef<[u8]> + ?Sized), field: &(impl AsRef<[u8]> + ?Sized)) -> usize {
+    field.as_ref().as_ptr() as usize - line.as_ref().as_ptr() as usize + 1
 }
 
 aoc_runner_derive::aoc_lib! { year = 2024 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn registry_is_in_day_order() {
+        let days = solutions().iter().map(|s| s.day()).collect::<Vec<_>>();
+        assert!(days.is_sorted());
+        assert_eq!(days.first(), Some(&1));
+    }
+
+    #[test]
+    fn registry_variants_are_unique() {
+        for (i, a) in solutions().iter().enumerate() {
+            for b in &solutions()[i + 1..] {
+                assert!(
+                    a.day() != b.day() || a.variant() != b.variant(),
+                    "day {} has two '{}' variants",
+                    a.day(),
+                    a.variant()
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn find_defaults_to_first_variant() {
+        assert_eq!(find(2, None).map(|s| s.variant()), Some("inline"));
+        assert_eq!(find(2, Some("naive")).map(|s| s.variant()), Some("naive"));
+        assert!(find(2, Some("missing")).is_none());
+        assert!(find(25, None).is_none());
+    }
+
+    #[test]
+    fn runs_through_registry() {
+        let parsed = find(1, None)
+            .unwrap()
+            .parse("3   4\n4   3\n2   5\n1   3\n3   9\n3   3")
+            .unwrap();
+        assert_eq!(parsed.part1(), Ok("11".to_string()));
+        assert_eq!(parsed.part2(), Ok("31".to_string()));
+    }
+
+    #[test]
+    fn solves_from_readers() {
+        for solution in (1..=25).filter_map(|day| find(day, None)) {
+            let day = solution.day();
+            let input = match day {
+                2 => "7 6 4 2 1\n1 3 2 4 5\n1 2 7 8 9",
+                3 => "xmul(2,4)&mul[3,7]!^don't()_mul(5,5)+mul(32,64](mul(11,8)undo()?mul(8,5))",
+                5 => "47|53\n97|13\n\n47,53,97\n53,97,47\n13,97,47",
+                _ => {
+                    assert!(solve_reader(day, 1, &mut "".as_bytes()).is_none());
+                    continue;
+                }
+            };
+
+            let parsed = solution.parse(input).unwrap();
+            for (part, expected) in [(1, parsed.part1()), (2, parsed.part2())] {
+                let answer = solve_reader(day, part, &mut input.as_bytes()).unwrap();
+                assert_eq!(answer.unwrap(), expected.unwrap(), "day {day} part {part}");
+            }
+        }
+    }
+
+    #[test]
+    fn validates_what_parses() {
+        let examples = [
+            (1, "3   4\n4   3\n2   5"),
+            (4, "XMAS\nSAMX"),
+            (5, "47|53\n97|13\n\n47,53,97\n53,97,47\n13,97,47"),
+            (6, "..#\n.^.\n..."),
+        ];
+        for (day, input) in examples {
+            assert_eq!(validate(day, input), Some(vec![]), "day {day}");
+            assert!(find(day, None).unwrap().parse(input).is_ok());
+        }
+        assert_eq!(validate(2, "1 2 3"), None);
+    }
+
+    #[test]
+    fn parse_field_rejects_what_is_not_a_u32() {
+        assert_eq!(parse_field(1, 1, 1, "91527", "a number"), Ok(91527));
+        assert_eq!(parse_field(1, 1, 1, "123456", "a number"), Ok(123456));
+        for field in ["", "4294967296", "12a", "-1"] {
+            assert_eq!(
+                parse_field(1, 2, 3, field, "a number"),
+                Err(ParseError::new(1, 2, 3, field, "a number"))
+            );
+        }
+    }
+}
@@ -0,0 +1,125 @@
+//! Loads puzzle inputs without copying them, by memory-mapping the file, so very large generated
+//! inputs cost no more than the solvers' own work.
+
+use std::{fs::File, io, path::Path, str::Utf8Error};
+
+use memmap2::Mmap;
+
+/// Where a day's input lives, relative to the crate root.
+pub fn input_path(day: u8) -> String {
+    format!("input/2024/day{day}.txt")
+}
+
+/// A puzzle input, either mapped from a file or held in memory when it came from somewhere that
+/// can't be mapped, like stdin.
+pub struct InputFile {
+    data: Data,
+}
+
+enum Data {
+    Mapped(Mmap),
+    Owned(Vec<u8>),
+}
+
+impl InputFile {
+    /// Maps the file at `path` into memory.
+    pub fn open(path: impl AsRef<Path>) -> io::Result<Self> {
+        let file = File::open(path)?;
+
+        // Mapping an empty file fails on some platforms, and there's nothing to save anyway.
+        if file.metadata()?.len() == 0 {
+            return Ok(InputFile::from(Vec::new()));
+        }
+
+        // SAFETY: the map is only ever read, and we expect nobody to change a puzzle input while
+        // we're solving it. If they did, the worst we'd see is garbled input, or a SIGBUS if
+        // the file shrank.
+        let map = unsafe { Mmap::map(&file)? };
+
+        Ok(InputFile {
+            data: Data::Mapped(map),
+        })
+    }
+
+    /// Maps the input for `day` from its usual place.
+    pub fn day(day: u8) -> io::Result<Self> {
+        InputFile::open(input_path(day))
+    }
+
+    /// Returns the raw input. Like cargo-aoc, we leave off trailing newlines.
+    pub fn bytes(&self) -> &[u8] {
+        let bytes = match &self.data {
+            Data::Mapped(map) => &map[..],
+            Data::Owned(bytes) => &bytes[..],
+        };
+        let len = bytes.iter().rposition(|b| *b != b'\n').map_or(0, |i| i + 1);
+        &bytes[..len]
+    }
+
+    /// Returns the input as text, checking that it's valid UTF-8.
+    pub fn text(&self) -> Result<&str, Utf8Error> {
+        std::str::from_utf8(self.bytes())
+    }
+
+    /// Whether the input is mapped from a file, rather than held in memory.
+    pub fn is_mapped(&self) -> bool {
+        matches!(self.data, Data::Mapped(_))
+    }
+}
+
+impl From<Vec<u8>> for InputFile {
+    fn from(bytes: Vec<u8>) -> Self {
+        InputFile {
+            data: Data::Owned(bytes),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{env, fs, process};
+
+    use super::*;
+
+    /// Writes `contents` to a file of its own in the temp directory, returning its path.
+    fn temp_file(name: &str, contents: &[u8]) -> std::path::PathBuf {
+        let path = env::temp_dir().join(format!("aoc2024-{}-{name}", process::id()));
+        fs::write(&path, contents).unwrap();
+        path
+    }
+
+    #[test]
+    fn maps_files() {
+        let path = temp_file("maps", b"1 2 3\n4 5 6\n\n");
+        let input = InputFile::open(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        assert!(input.is_mapped());
+        assert_eq!(input.bytes(), b"1 2 3\n4 5 6");
+        assert_eq!(input.text(), Ok("1 2 3\n4 5 6"));
+        assert_eq!(crate::day2::part1(input.text().unwrap()), Ok(2));
+    }
+
+    #[test]
+    fn handles_empty_and_binary_files() {
+        let path = temp_file("empty", b"");
+        let input = InputFile::open(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        assert_eq!(input.text(), Ok(""));
+
+        let path = temp_file("binary", b"mul(2,3)\xff");
+        let input = InputFile::open(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        assert!(input.text().is_err());
+        assert_eq!(input.bytes(), b"mul(2,3)\xff");
+
+        assert!(InputFile::open("no/such/input.txt").is_err());
+    }
+
+    #[test]
+    fn holds_other_inputs() {
+        let input = InputFile::from(b"ab\n".to_vec());
+        assert!(!input.is_mapped());
+        assert_eq!(input.text(), Ok("ab"));
+    }
+}
@@ -1,3 +1,855 @@
-use aoc_runner_derive::aoc_main;
+use std::{
+    env, fs,
+    io::{self, BufReader, Read},
+    process::ExitCode,
+    sync::atomic::{AtomicUsize, Ordering},
+    thread,
+    time::{Duration, Instant},
+};
 
-aoc_main! { lib = aoc2024 }
+use aoc2024::{
+    answers,
+    answers::Answers,
+    find,
+    load::{input_path, InputFile},
+    solutions, solve_reader, Parsed, Runner,
+};
+use report::{Format, HashingReader, Record};
+
+mod report;
+
+const USAGE: &str = "Usage:
+    aoc2024 run (--day N [--part P] | --all) [--variant NAME] [--input PATH] [--format FORMAT]
+        [--parallel [--jobs N] [--budget-ms MS]]
+    aoc2024 run --day N [--part P] --stream [--input PATH] [--format FORMAT]
+    aoc2024 bench [--day N [--input PATH]] [--variant NAME] [--iterations N]
+    aoc2024 verify --day N --part P --expect ANSWER [--variant NAME] [--input PATH]
+    aoc2024 verify [--day N] [--part P] [--variant NAME] [--answers PATH] [--record]
+    aoc2024 validate (--day N | --all) [--input PATH]
+    aoc2024 explain --day 1 [--part P] [--top K] [--input PATH] [--format FORMAT]
+
+Inputs are read from input/2024/dayN.txt unless --input is given. Use `--input -` to read stdin.
+run prints its answers and timings as text, json or csv. With --parallel, every part is run at
+once on a pool of threads, followed by a summary of where the time went. Parts that take longer
+than --budget-ms to solve, not counting parsing, are flagged, and make the run fail. With
+--stream, days 2, 3 and 5 solve while reading, rather than reading the whole input first, so
+inputs of any size can be used. Streaming from stdin needs --part, as the input can only be read
+once.
+Without --expect, verify checks every selected solution against answers/2024.toml. With --record,
+answers missing from that file are filled in from the default variant of each day.
+validate checks the structure of the input for days 1, 4, 5 and 6, listing every problem found.
+explain lists what goes into day 1's answers, as text or json: each pair and its distance for
+part 1, and each ID in both lists with its counts and score for part 2. With --top, only the K
+biggest contributors to each answer are listed.";
+
+#[derive(Debug, PartialEq)]
+enum Command {
+    Run,
+    Bench,
+    Verify,
+    Validate,
+    Explain,
+}
+
+#[derive(Debug)]
+struct Args {
+    command: Command,
+    day: Option<u8>,
+    part: Option<u8>,
+    variant: Option<String>,
+    all: bool,
+    input: Option<String>,
+    iterations: u32,
+    expect: Option<String>,
+    answers: Option<String>,
+    record: bool,
+    format: Format,
+    parallel: bool,
+    jobs: Option<usize>,
+    budget: Option<Duration>,
+    stream: bool,
+    top: Option<usize>,
+}
+
+fn parse_args(mut args: impl Iterator<Item = String>) -> Result<Args, String> {
+    let command = match args.next().as_deref() {
+        Some("run") => Command::Run,
+        Some("bench") => Command::Bench,
+        Some("verify") => Command::Verify,
+        Some("validate") => Command::Validate,
+        Some("explain") => Command::Explain,
+        Some(other) => return Err(format!("unknown command '{other}'")),
+        None => return Err("missing command".to_string()),
+    };
+
+    let mut parsed = Args {
+        command,
+        day: None,
+        part: None,
+        variant: None,
+        all: false,
+        input: None,
+        iterations: 100,
+        expect: None,
+        answers: None,
+        record: false,
+        format: Format::Text,
+        parallel: false,
+        jobs: None,
+        budget: None,
+        stream: false,
+        top: None,
+    };
+
+    while let Some(flag) = args.next() {
+        let mut value = || args.next().ok_or_else(|| format!("{flag} needs a value"));
+
+        match flag.as_str() {
+            "--day" | "-d" => parsed.day = Some(parse_flag(&flag, value()?)?),
+            "--part" | "-p" => parsed.part = Some(parse_flag(&flag, value()?)?),
+            "--variant" => parsed.variant = Some(value()?),
+            "--input" | "-i" => parsed.input = Some(value()?),
+            "--iterations" => parsed.iterations = parse_flag(&flag, value()?)?,
+            "--expect" => parsed.expect = Some(value()?),
+            "--format" => {
+                let format = value()?;
+                parsed.format = format
+                    .parse()
+                    .map_err(|_| format!("unknown format '{format}'"))?;
+            }
+            "--answers" => parsed.answers = Some(value()?),
+            "--jobs" | "-j" => parsed.jobs = Some(parse_flag(&flag, value()?)?),
+            "--top" => parsed.top = Some(parse_flag(&flag, value()?)?),
+            "--budget-ms" => {
+                let ms: f64 = parse_flag(&flag, value()?)?;
+                parsed.budget = Some(
+                    Duration::try_from_secs_f64(ms / 1000.0)
+                        .map_err(|_| format!("invalid value '{ms}' for {flag}"))?,
+                );
+            }
+            "--all" => parsed.all = true,
+            "--parallel" => parsed.parallel = true,
+            "--record" => parsed.record = true,
+            "--stream" => parsed.stream = true,
+            _ => return Err(format!("unknown flag '{flag}'")),
+        }
+    }
+
+    if !matches!(parsed.part, None | Some(1) | Some(2)) {
+        return Err("--part must be 1 or 2".to_string());
+    }
+    if parsed.jobs == Some(0) {
+        return Err("--jobs must be at least 1".to_string());
+    }
+    if !parsed.parallel && (parsed.jobs.is_some() || parsed.budget.is_some()) {
+        return Err("--jobs and --budget-ms need --parallel".to_string());
+    }
+    if parsed.stream && (parsed.day.is_none() || parsed.all || parsed.parallel) {
+        return Err("--stream needs a single --day, and can't be run in --parallel".to_string());
+    }
+    if parsed.stream && parsed.variant.is_some() {
+        return Err("--stream has no variants".to_string());
+    }
+    if parsed.stream && parsed.part.is_none() && parsed.input.as_deref() == Some("-") {
+        return Err("--stream from stdin needs --part".to_string());
+    }
+
+    Ok(parsed)
+}
+
+fn parse_flag<T: std::str::FromStr>(flag: &str, value: String) -> Result<T, String> {
+    value
+        .parse()
+        .map_err(|_| format!("invalid value '{value}' for {flag}"))
+}
+
+fn main() -> ExitCode {
+    let args = match parse_args(env::args().skip(1)) {
+        Ok(args) => args,
+        Err(e) => {
+            eprintln!("{e}\n\n{USAGE}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let res = match args.command {
+        Command::Run => run(&args),
+        Command::Bench => bench(&args),
+        Command::Verify => verify(&args),
+        Command::Validate => validate(&args),
+        Command::Explain => explain(&args),
+    };
+
+    match res {
+        Ok(code) => code,
+        Err(e) => {
+            eprintln!("{e}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// Picks the solutions a command applies to. Without `--day`, that's every registered solution,
+/// narrowed down to one implementation per day when `--variant` is given.
+fn select(args: &Args) -> Result<Vec<&'static dyn Runner>, String> {
+    match args.day {
+        Some(day) => find(day, args.variant.as_deref())
+            .map(|s| vec![s])
+            .ok_or_else(|| match &args.variant {
+                Some(v) => format!("day {day} has no '{v}' variant"),
+                None => format!("day {day} is not implemented"),
+            }),
+        None => Ok(solutions()
+            .iter()
+            .copied()
+            .filter(|s| args.variant.as_ref().is_none_or(|v| v == s.variant()))
+            .collect()),
+    }
+}
+
+/// Loads a day's input, mapping files into memory rather than reading them. Like cargo-aoc,
+/// trailing newlines are stripped before the solutions see it.
+fn read_input(day: u8, path: Option<&str>) -> Result<InputFile, String> {
+    match path {
+        Some("-") => {
+            let mut buf = Vec::new();
+            io::stdin()
+                .read_to_end(&mut buf)
+                .map_err(|e| format!("reading stdin: {e}"))?;
+            Ok(InputFile::from(buf))
+        }
+        Some(path) => InputFile::open(path).map_err(|e| format!("reading {path}: {e}")),
+        None => {
+            let path = input_path(day);
+            InputFile::open(&path).map_err(|e| format!("reading {path}: {e}"))
+        }
+    }
+}
+
+/// Borrows the text of an input, which the solutions need to be UTF-8.
+fn text(input: &InputFile) -> Result<&str, String> {
+    input
+        .text()
+        .map_err(|e| format!("input is not valid UTF-8: {e}"))
+}
+
+fn solve(parsed: &dyn Parsed, part: u8) -> Result<String, String> {
+    match part {
+        1 => parsed.part1(),
+        _ => parsed.part2(),
+    }
+    .map_err(|e| e.to_string())
+}
+
+fn run(args: &Args) -> Result<ExitCode, String> {
+    if args.day.is_none() && !args.all {
+        return Err(format!("run needs --day or --all\n\n{USAGE}"));
+    }
+    if args.all && args.input.is_some() {
+        return Err("--input can only be used with a single --day".to_string());
+    }
+
+    let parts = args.part.map_or(vec![1, 2], |p| vec![p]);
+    if args.parallel {
+        return run_parallel(args, &parts);
+    }
+    if let (true, Some(day)) = (args.stream, args.day) {
+        return run_stream(args, day, &parts);
+    }
+
+    let mut records = Vec::new();
+    // Days that fail to parse or solve are skipped when running everything, but still fail the run.
+    let mut failed = false;
+
+    for solution in select(args)? {
+        let file = read_input(solution.day(), args.input.as_deref());
+        let input = match file.as_ref().map_err(String::clone).and_then(text) {
+            Ok(input) => input,
+            // Carry on with the other days when running everything, as we may not have fetched
+            // every input yet.
+            Err(e) if args.all => {
+                eprintln!("Skipping day {}: {e}", solution.day());
+                continue;
+            }
+            Err(e) => return Err(e),
+        };
+
+        let start = Instant::now();
+        let parsed = match solution.parse(input) {
+            Ok(parsed) => parsed,
+            Err(e) if args.all => {
+                eprintln!("Skipping day {}: {e}", solution.day());
+                failed = true;
+                continue;
+            }
+            Err(e) => return Err(e.to_string()),
+        };
+        let parse_time = start.elapsed();
+        let input_hash = report::input_hash(input);
+
+        for &part in &parts {
+            let start = Instant::now();
+            let answer = match solve(parsed.as_ref(), part) {
+                Ok(answer) => answer,
+                Err(e) if args.all => {
+                    eprintln!("Skipping day {} part {part}: {e}", solution.day());
+                    failed = true;
+                    continue;
+                }
+                Err(e) => return Err(e),
+            };
+            let solve_time = start.elapsed();
+
+            let record = Record {
+                day: solution.day(),
+                part,
+                variant: solution.variant(),
+                answer,
+                parse_time,
+                solve_time,
+                input_hash,
+            };
+
+            // Show answers as we get them, rather than waiting for the slow days.
+            if args.format == Format::Text {
+                print!("{}", args.format.render(std::slice::from_ref(&record)));
+            }
+            records.push(record);
+        }
+    }
+
+    if args.format != Format::Text {
+        print!("{}", args.format.render(&records));
+    }
+
+    Ok(if failed {
+        ExitCode::FAILURE
+    } else {
+        ExitCode::SUCCESS
+    })
+}
+
+/// Solves each part while reading its input, opening the input afresh for each part.
+fn run_stream(args: &Args, day: u8, parts: &[u8]) -> Result<ExitCode, String> {
+    let mut records = Vec::new();
+
+    for &part in parts {
+        let input: Box<dyn Read> = match args.input.as_deref() {
+            Some("-") => Box::new(io::stdin().lock()),
+            path => {
+                let path = path.map_or_else(|| input_path(day), str::to_string);
+                Box::new(fs::File::open(&path).map_err(|e| format!("reading {path}: {e}"))?)
+            }
+        };
+        let mut input = HashingReader::new(BufReader::with_capacity(1 << 16, input));
+
+        let start = Instant::now();
+        let answer = solve_reader(day, part, &mut input)
+            .ok_or_else(|| format!("day {day} can't be solved from a stream"))?
+            .map_err(|e| e.to_string())?;
+        let solve_time = start.elapsed();
+
+        let record = Record {
+            day,
+            part,
+            variant: "stream",
+            answer,
+            parse_time: Duration::ZERO,
+            solve_time,
+            input_hash: input.hash(),
+        };
+
+        if args.format == Format::Text {
+            print!("{}", args.format.render(std::slice::from_ref(&record)));
+        }
+        records.push(record);
+    }
+
+    if args.format != Format::Text {
+        print!("{}", args.format.render(&records));
+    }
+
+    Ok(ExitCode::SUCCESS)
+}
+
+/// Runs every selected part at once on a pool of threads. Each input is parsed once, also on the
+/// pool, and then shared by its parts so the two parts of a day can run side by side. The parse
+/// time is counted against the first of them, so the summary doesn't count it twice.
+fn run_parallel(args: &Args, parts: &[u8]) -> Result<ExitCode, String> {
+    let mut inputs = Vec::new();
+    for solution in select(args)? {
+        match read_input(solution.day(), args.input.as_deref()) {
+            Ok(file) => inputs.push((solution, file)),
+            Err(e) if args.all => eprintln!("Skipping day {}: {e}", solution.day()),
+            Err(e) => return Err(e),
+        }
+    }
+
+    let mut texts = Vec::new();
+    for (solution, file) in &inputs {
+        match text(file) {
+            Ok(input) => texts.push((*solution, input)),
+            Err(e) if args.all => eprintln!("Skipping day {}: {e}", solution.day()),
+            Err(e) => return Err(e),
+        }
+    }
+    let jobs = args
+        .jobs
+        .unwrap_or_else(|| thread::available_parallelism().map_or(1, |n| n.get()));
+
+    let start = Instant::now();
+    let parsed = par_map(&texts, jobs, |&(solution, input)| {
+        let start = Instant::now();
+        let parsed = solution.parse(input);
+        (parsed, start.elapsed(), report::input_hash(input))
+    });
+
+    // Days that fail to parse or solve are left out of the records, but still fail the run.
+    let mut errors = Vec::new();
+    let mut tasks = Vec::new();
+    for ((solution, _), (parsed, parse_time, input_hash)) in texts.iter().zip(&parsed) {
+        match parsed {
+            Ok(parsed) => tasks.extend(parts.iter().enumerate().map(|(i, &part)| {
+                let parse_time = if i == 0 { *parse_time } else { Duration::ZERO };
+                (*solution, parsed.as_ref(), part, parse_time, *input_hash)
+            })),
+            Err(e) => errors.push(format!(
+                "day {} ({}): {e}",
+                solution.day(),
+                solution.variant()
+            )),
+        }
+    }
+
+    let results = par_map(
+        &tasks,
+        jobs,
+        |&(solution, parsed, part, parse_time, input_hash)| {
+            let start = Instant::now();
+            let answer = solve(parsed, part).map_err(|e| {
+                format!(
+                    "day {} part {part} ({}): {e}",
+                    solution.day(),
+                    solution.variant()
+                )
+            })?;
+            let solve_time = start.elapsed();
+
+            Ok::<_, String>(Record {
+                day: solution.day(),
+                part,
+                variant: solution.variant(),
+                answer,
+                parse_time,
+                solve_time,
+                input_hash,
+            })
+        },
+    );
+    let wall_time = start.elapsed();
+
+    let mut records = Vec::with_capacity(results.len());
+    for result in results {
+        match result {
+            Ok(record) => records.push(record),
+            Err(e) => errors.push(e),
+        }
+    }
+
+    print!("{}", args.format.render(&records));
+
+    // Keep stdout machine-readable when asked for json or csv.
+    let summary = report::summary(&records, &errors, wall_time, args.budget);
+    if args.format == Format::Text {
+        print!("{summary}");
+    } else {
+        eprint!("{summary}");
+    }
+
+    let over_budget = records.iter().any(|r| r.over_budget(args.budget));
+
+    Ok(if over_budget || !errors.is_empty() {
+        ExitCode::FAILURE
+    } else {
+        ExitCode::SUCCESS
+    })
+}
+
+/// Maps `f` over `items` using up to `jobs` threads, each taking the next unclaimed item until
+/// there are none left. Results come back in the same order as `items`.
+fn par_map<T: Sync, R: Send>(items: &[T], jobs: usize, f: impl Fn(&T) -> R + Sync) -> Vec<R> {
+    let next = AtomicUsize::new(0);
+    let mut results = (0..items.len()).map(|_| None).collect::<Vec<_>>();
+
+    thread::scope(|s| {
+        let workers = (0..jobs.min(items.len()))
+            .map(|_| {
+                s.spawn(|| {
+                    let mut done = Vec::new();
+                    loop {
+                        let i = next.fetch_add(1, Ordering::Relaxed);
+                        let Some(item) = items.get(i) else {
+                            return done;
+                        };
+                        done.push((i, f(item)));
+                    }
+                })
+            })
+            .collect::<Vec<_>>();
+
+        for worker in workers {
+            for (i, r) in worker.join().expect("worker thread panicked") {
+                results[i] = Some(r);
+            }
+        }
+    });
+
+    results.into_iter().map(Option::unwrap).collect()
+}
+
+fn bench(args: &Args) -> Result<ExitCode, String> {
+    if args.day.is_none() && args.input.is_some() {
+        return Err("--input can only be used with a single --day".to_string());
+    }
+    let iterations = args.iterations.max(1);
+
+    println!(
+        "{:>3}  {:<10}  {:>12}  {:>12}  {:>12}",
+        "day", "variant", "parse", "part 1", "part 2"
+    );
+
+    for solution in select(args)? {
+        let file = read_input(solution.day(), args.input.as_deref());
+        let input = match file.as_ref().map_err(String::clone).and_then(text) {
+            Ok(input) => input,
+            // Input we were pointed at has to be there, but we may not have fetched every day's.
+            Err(e) if args.input.is_some() => return Err(e),
+            Err(e) => {
+                eprintln!("Skipping day {}: {e}", solution.day());
+                continue;
+            }
+        };
+
+        let parsed = solution.parse(input).map_err(|e| e.to_string())?;
+        let parse_time = time(iterations, || drop(solution.parse(input)));
+        let part1_time = time(iterations, || drop(parsed.part1()));
+        let part2_time = time(iterations, || drop(parsed.part2()));
+
+        println!(
+            "{:>3}  {:<10}  {:>12?}  {:>12?}  {:>12?}",
+            solution.day(),
+            solution.variant(),
+            parse_time,
+            part1_time,
+            part2_time
+        );
+    }
+
+    Ok(ExitCode::SUCCESS)
+}
+
+/// Returns the mean time taken by `f` over the given number of iterations.
+fn time(iterations: u32, mut f: impl FnMut()) -> Duration {
+    let start = Instant::now();
+    for _ in 0..iterations {
+        f();
+    }
+    start.elapsed() / iterations
+}
+
+fn verify(args: &Args) -> Result<ExitCode, String> {
+    if args.expect.is_some() {
+        return verify_expected(args);
+    }
+    if args.day.is_none() && args.input.is_some() {
+        return Err("--input can only be used with a single --day".to_string());
+    }
+    if args.record && args.variant.is_some() {
+        return Err("--record always uses the default variant of each day".to_string());
+    }
+
+    let path = args.answers.as_deref().unwrap_or(answers::DEFAULT_PATH);
+    let mut answers = match fs::read_to_string(path) {
+        Ok(s) => Answers::parse(&s).map_err(|e| format!("{path}: {e}"))?,
+        Err(e) if args.record && e.kind() == io::ErrorKind::NotFound => Answers::default(),
+        Err(e) => return Err(format!("reading {path}: {e}")),
+    };
+    if answers.is_empty() && !args.record {
+        return Err(format!(
+            "{path} has no answers to check, run verify --record to fill them in"
+        ));
+    }
+
+    let parts = args.part.map_or(vec![1, 2], |p| vec![p]);
+    let (mut checked, mut mismatches, mut recorded) = (0, 0, 0);
+
+    for solution in select(args)? {
+        let day = solution.day();
+        let file = read_input(day, args.input.as_deref());
+        let input = match file.as_ref().map_err(String::clone).and_then(text) {
+            Ok(input) => input,
+            Err(e) => {
+                eprintln!("Skipping day {day}: {e}");
+                continue;
+            }
+        };
+        let parsed = match solution.parse(input) {
+            Ok(parsed) => parsed,
+            Err(e) => {
+                // Input we can't parse or solve counts as a wrong answer.
+                mismatches += parts.len();
+                println!("Day {day} ({}) : {e}", solution.variant());
+                continue;
+            }
+        };
+
+        for &part in &parts {
+            let actual = match solve(parsed.as_ref(), part) {
+                Ok(actual) => actual,
+                Err(e) => {
+                    mismatches += 1;
+                    println!("Day {day} - Part {part} ({}) : {e}", solution.variant());
+                    continue;
+                }
+            };
+
+            match answers.get(day, part) {
+                Some(expected) if expected == actual => {
+                    checked += 1;
+                    println!("Day {day} - Part {part} ({}) : ok", solution.variant());
+                }
+                Some(expected) => {
+                    checked += 1;
+                    mismatches += 1;
+                    println!(
+                        "Day {day} - Part {part} ({}) : expected {expected}, got {actual}",
+                        solution.variant()
+                    );
+                }
+                None if args.record => {
+                    recorded += 1;
+                    println!(
+                        "Day {day} - Part {part} ({}) : recorded {actual}",
+                        solution.variant()
+                    );
+                    answers.insert(day, part, actual);
+                }
+                None => println!(
+                    "Day {day} - Part {part} ({}) : no answer recorded",
+                    solution.variant()
+                ),
+            }
+        }
+    }
+
+    if recorded > 0 {
+        fs::write(path, answers.to_string()).map_err(|e| format!("writing {path}: {e}"))?;
+    }
+
+    println!("\n{checked} checked, {mismatches} wrong, {recorded} recorded");
+
+    // Checking nothing shouldn't pass for checking everything, unless we've just recorded it.
+    if checked == 0 && recorded == 0 {
+        eprintln!("No answers were checked");
+        return Ok(ExitCode::FAILURE);
+    }
+
+    Ok(if mismatches == 0 {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    })
+}
+
+/// Checks the structure of each selected day's input, listing every problem rather than stopping
+/// at the first. Fails if any input has problems.
+fn validate(args: &Args) -> Result<ExitCode, String> {
+    let days = match args.day {
+        Some(day) => vec![day],
+        None if args.all => (1..=25).filter(|day| find(*day, None).is_some()).collect(),
+        None => return Err(format!("validate needs --day or --all\n\n{USAGE}")),
+    };
+    if args.all && args.input.is_some() {
+        return Err("--input can only be used with a single --day".to_string());
+    }
+
+    let mut invalid = 0;
+
+    for day in days {
+        let file = read_input(day, args.input.as_deref());
+        let input = match file.as_ref().map_err(String::clone).and_then(text) {
+            Ok(input) => input,
+            Err(e) if args.all => {
+                eprintln!("Skipping day {day}: {e}");
+                continue;
+            }
+            Err(e) => return Err(e),
+        };
+
+        match aoc2024::validate(day, input) {
+            Some(errors) if errors.is_empty() => println!("Day {day} : valid"),
+            Some(errors) => {
+                invalid += 1;
+                println!("Day {day} : invalid");
+                for e in errors {
+                    println!("    {e}");
+                }
+            }
+            None if args.all => {}
+            None => return Err(format!("day {day} has no input checks")),
+        }
+    }
+
+    Ok(if invalid == 0 {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    })
+}
+
+/// Lists what goes into each of day 1's answers, so a surprising one can be traced back to the
+/// input.
+fn explain(args: &Args) -> Result<ExitCode, String> {
+    match args.day {
+        Some(1) => {}
+        Some(day) => return Err(format!("day {day} can't be explained, only day 1")),
+        None => return Err(format!("explain needs --day 1\n\n{USAGE}")),
+    }
+    if args.format == Format::Csv {
+        return Err("explain can only show text or json".to_string());
+    }
+
+    let file = read_input(1, args.input.as_deref())?;
+    let explanation = aoc2024::day1::explain(text(&file)?).map_err(|e| e.to_string())?;
+    let parts = args.part.map_or(vec![1, 2], |p| vec![p]);
+    print!(
+        "{}",
+        report::explanation(&explanation, &parts, args.top, args.format)
+    );
+
+    Ok(ExitCode::SUCCESS)
+}
+
+/// Checks a single answer given on the command line.
+fn verify_expected(args: &Args) -> Result<ExitCode, String> {
+    let (Some(day), Some(part), Some(expected)) = (args.day, args.part, args.expect.as_ref())
+    else {
+        return Err(format!("verify needs --day, --part and --expect\n\n{USAGE}"));
+    };
+
+    let solution = find(day, args.variant.as_deref())
+        .ok_or_else(|| format!("day {day} has no matching solution"))?;
+    let file = read_input(day, args.input.as_deref())?;
+    let parsed = solution.parse(text(&file)?).map_err(|e| e.to_string())?;
+    let actual = solve(parsed.as_ref(), part)?;
+
+    if &actual == expected {
+        println!("Day {day} - Part {part} ({}) : ok", solution.variant());
+        Ok(ExitCode::SUCCESS)
+    } else {
+        println!(
+            "Day {day} - Part {part} ({}) : expected {expected}, got {actual}",
+            solution.variant()
+        );
+        Ok(ExitCode::FAILURE)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(s: &str) -> Result<Args, String> {
+        parse_args(s.split_whitespace().map(String::from))
+    }
+
+    #[test]
+    fn parses_run() {
+        let a = args("run --day 2 --part 1 --variant naive --input -").unwrap();
+        assert_eq!(a.command, Command::Run);
+        assert_eq!(a.day, Some(2));
+        assert_eq!(a.part, Some(1));
+        assert_eq!(a.variant.as_deref(), Some("naive"));
+        assert_eq!(a.input.as_deref(), Some("-"));
+        assert_eq!(a.format, Format::Text);
+        assert!(!a.all);
+
+        assert_eq!(args("run --all --format csv").unwrap().format, Format::Csv);
+    }
+
+    #[test]
+    fn rejects_bad_arguments() {
+        assert!(args("").is_err());
+        assert!(args("frobnicate").is_err());
+        assert!(args("run --day").is_err());
+        assert!(args("run --day x").is_err());
+        assert!(args("run --part 3").is_err());
+        assert!(args("run --colour").is_err());
+        assert!(args("run --format xml").is_err());
+        assert!(args("run --all --budget-ms 5").is_err());
+        assert!(args("run --all --parallel --jobs 0").is_err());
+        assert!(args("run --all --parallel --budget-ms -1").is_err());
+        assert!(args("run --all --stream").is_err());
+        assert!(args("run --day 2 --stream --parallel").is_err());
+        assert!(args("run --day 2 --stream --variant naive").is_err());
+        assert!(args("run --day 2 --stream --input -").is_err());
+        assert!(args("run --day 2 --part 1 --stream --input -").unwrap().stream);
+    }
+
+    #[test]
+    fn parses_validate() {
+        let a = args("validate --day 5 --input -").unwrap();
+        assert_eq!(a.command, Command::Validate);
+        assert_eq!(a.day, Some(5));
+        assert!(args("validate --all").unwrap().all);
+    }
+
+    #[test]
+    fn parses_explain() {
+        let a = args("explain --day 1 --part 2 --top 5 --format json").unwrap();
+        assert_eq!(a.command, Command::Explain);
+        assert_eq!(a.part, Some(2));
+        assert_eq!(a.top, Some(5));
+        assert_eq!(a.format, Format::Json);
+        assert!(args("explain --day 1 --top -1").is_err());
+    }
+
+    #[test]
+    fn parses_parallel_options() {
+        let a = args("run --all --parallel -j 3 --budget-ms 2.5").unwrap();
+        assert!(a.parallel);
+        assert_eq!(a.jobs, Some(3));
+        assert_eq!(a.budget, Some(Duration::from_micros(2500)));
+    }
+
+    #[test]
+    fn par_map_keeps_order() {
+        let items = (0..100).collect::<Vec<u32>>();
+        for jobs in [1, 3, 200] {
+            assert_eq!(
+                par_map(&items, jobs, |i| i * 2),
+                items.iter().map(|i| i * 2).collect::<Vec<_>>()
+            );
+        }
+        assert!(par_map(&[] as &[u32], 4, |i| *i).is_empty());
+    }
+
+    #[test]
+    fn benches_other_input_for_one_day() {
+        assert!(bench(&args("bench --input day1.txt").unwrap()).is_err());
+    }
+
+    #[test]
+    fn selects_one_variant_per_day() {
+        let a = args("bench --variant naive").unwrap();
+        let selected = select(&a).unwrap();
+        assert_eq!(selected.len(), 1);
+        assert_eq!(selected[0].day(), 2);
+
+        assert!(select(&args("run --day 25").unwrap()).is_err());
+        assert_eq!(
+            select(&args("run --all").unwrap()).unwrap().len(),
+            solutions().len()
+        );
+    }
+}
@@ -0,0 +1,241 @@
+//! Fast number parsing for puzzle inputs, which are nearly always short runs of ASCII digits.
+//!
+//! [`parse`] trusts its input and skips all validation, [`parse_checked`] reports anything that
+//! isn't a number that fits, and [`parse_signed`] also accepts a leading sign.
+
+use std::{error::Error, fmt};
+
+/// Powers of 10 up to the largest that fits in a `u64`, so each digit can be scaled
+/// independently rather than folding through the number one digit at a time.
+const POWERS_OF_10: [u64; 20] = {
+    let mut powers = [1; 20];
+    let mut i = 1;
+    while i < powers.len() {
+        powers[i] = powers[i - 1] * 10;
+        i += 1;
+    }
+    powers
+};
+
+/// An unsigned integer type we can parse into.
+pub trait Unsigned: Copy + TryFrom<u64> {
+    /// The most digits that always fit in the type, whatever they are.
+    const MAX_DIGITS: usize;
+
+    /// Converts from a `u64`, keeping only the low bits when it doesn't fit.
+    fn truncate(value: u64) -> Self;
+}
+
+macro_rules! unsigned {
+    ($($t:ty => $digits:expr),*) => {
+        $(
+            impl Unsigned for $t {
+                const MAX_DIGITS: usize = $digits;
+
+                fn truncate(value: u64) -> Self {
+                    value as $t
+                }
+            }
+        )*
+    };
+}
+
+unsigned!(u16 => 4, u32 => 9, u64 => 19);
+
+/// Why some text isn't a number of the type we asked for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NumberError {
+    /// There weren't any digits.
+    Empty,
+    /// The byte at this index isn't an ASCII digit.
+    InvalidDigit(usize),
+    /// The number is too big, or too small, for the type.
+    Overflow,
+}
+
+impl fmt::Display for NumberError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            NumberError::Empty => f.write_str("no digits"),
+            NumberError::InvalidDigit(i) => write!(f, "byte {i} is not a digit"),
+            NumberError::Overflow => f.write_str("number out of range"),
+        }
+    }
+}
+
+impl Error for NumberError {}
+
+/// Parses `digits` without any checks. The caller must make sure they're all ASCII digits and
+/// that the number fits in `T`; otherwise the result is meaningless, though it won't panic.
+pub fn parse<T: Unsigned>(digits: &[u8]) -> T {
+    let value = if digits.len() < POWERS_OF_10.len() {
+        let radix = digits.len().wrapping_sub(1);
+        digits.iter().enumerate().fold(0_u64, |sum, (n, d)| {
+            sum.wrapping_add(d.wrapping_sub(b'0') as u64 * POWERS_OF_10[radix - n])
+        })
+    } else {
+        digits.iter().fold(0_u64, |acc, d| {
+            acc.wrapping_mul(10)
+                .wrapping_add(d.wrapping_sub(b'0') as u64)
+        })
+    };
+
+    T::truncate(value)
+}
+
+/// Parses `digits`, reporting anything other than ASCII digits and numbers too big for `T`.
+pub fn parse_checked<T: Unsigned>(digits: &[u8]) -> Result<T, NumberError> {
+    if digits.is_empty() {
+        return Err(NumberError::Empty);
+    }
+    if let Some(i) = digits.iter().position(|d| !d.is_ascii_digit()) {
+        return Err(NumberError::InvalidDigit(i));
+    }
+
+    if digits.len() <= T::MAX_DIGITS {
+        return Ok(parse(digits));
+    }
+
+    digits
+        .iter()
+        .try_fold(0_u64, |acc, d| {
+            acc.checked_mul(10)?.checked_add((d - b'0') as u64)
+        })
+        .and_then(|value| T::try_from(value).ok())
+        .ok_or(NumberError::Overflow)
+}
+
+/// Parses up to 8 ASCII digits at once with SWAR ("SIMD within a register") arithmetic on a
+/// single `u64`, instead of a digit at a time. Returns `None` if any byte isn't a digit.
+pub fn parse_swar(digits: &[u8]) -> Option<u32> {
+    if !(1..=8).contains(&digits.len()) {
+        return None;
+    }
+
+    // Pad on the left with zeros, which don't change the value. Reading little endian puts the
+    // first digit in the lowest byte on every platform.
+    let mut padded = [b'0'; 8];
+    padded[8 - digits.len()..].copy_from_slice(digits);
+    let chunk = u64::from_le_bytes(padded);
+
+    // Every byte of a digit has a high nibble of 3, and still does after adding 6.
+    let high = 0xF0F0_F0F0_F0F0_F0F0;
+    if chunk & high | (chunk.wrapping_add(0x0606_0606_0606_0606) & high) >> 4
+        != 0x3333_3333_3333_3333
+    {
+        return None;
+    }
+
+    // Combine neighbouring digits into 2-digit numbers, then those into 4-digit numbers, and
+    // finally into the whole 8 digits, each time in a single multiply.
+    let chunk = chunk - 0x3030_3030_3030_3030;
+    let chunk = (chunk * 10 + (chunk >> 8)) & 0x00FF_00FF_00FF_00FF;
+    let chunk = (chunk * 100 + (chunk >> 16)) & 0x0000_FFFF_0000_FFFF;
+    let chunk = (chunk * 10000 + (chunk >> 32)) & 0xFFFF_FFFF;
+
+    Some(chunk as u32)
+}
+
+/// Parses a number with an optional leading `+` or `-`.
+pub fn parse_signed<T: TryFrom<i128>>(text: &[u8]) -> Result<T, NumberError> {
+    let (negative, digits) = match text {
+        [b'-', rest @ ..] => (true, rest),
+        [b'+', rest @ ..] => (false, rest),
+        _ => (false, text),
+    };
+    let sign_len = text.len() - digits.len();
+
+    let magnitude = parse_checked::<u64>(digits).map_err(|e| match e {
+        NumberError::InvalidDigit(i) => NumberError::InvalidDigit(i + sign_len),
+        e => e,
+    })? as i128;
+
+    T::try_from(if negative { -magnitude } else { magnitude }).map_err(|_| NumberError::Overflow)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_any_width() {
+        assert_eq!(parse::<u32>(b"0"), 0);
+        assert_eq!(parse::<u32>(b"91527"), 91527);
+        assert_eq!(parse::<u32>(b"123456"), 123456);
+        assert_eq!(parse::<u64>(b"18446744073709551615"), u64::MAX);
+        assert_eq!(POWERS_OF_10[19], 10_000_000_000_000_000_000);
+
+        for n in [0, 7, 42, 65535, 1_000_000, u32::MAX as u64, u64::MAX] {
+            assert_eq!(parse::<u64>(n.to_string().as_bytes()), n);
+            assert_eq!(parse_checked::<u64>(n.to_string().as_bytes()), Ok(n));
+        }
+    }
+
+    #[test]
+    fn checks_digits_and_range() {
+        assert_eq!(parse_checked::<u16>(b"65535"), Ok(65535));
+        assert_eq!(parse_checked::<u16>(b"0065535"), Ok(65535));
+        assert_eq!(parse_checked::<u16>(b"65536"), Err(NumberError::Overflow));
+        assert_eq!(
+            parse_checked::<u32>(b"4294967296"),
+            Err(NumberError::Overflow)
+        );
+        assert_eq!(
+            parse_checked::<u64>(b"18446744073709551616"),
+            Err(NumberError::Overflow)
+        );
+        assert_eq!(
+            parse_checked::<u64>(b"999999999999999999999"),
+            Err(NumberError::Overflow)
+        );
+        assert_eq!(parse_checked::<u32>(b""), Err(NumberError::Empty));
+        assert_eq!(
+            parse_checked::<u32>(b"12a"),
+            Err(NumberError::InvalidDigit(2))
+        );
+        assert_eq!(
+            parse_checked::<u32>(b"-1"),
+            Err(NumberError::InvalidDigit(0))
+        );
+    }
+
+    #[test]
+    fn parses_with_swar() {
+        assert_eq!(parse_swar(b"0"), Some(0));
+        assert_eq!(parse_swar(b"91527"), Some(91527));
+        assert_eq!(parse_swar(b"12345678"), Some(12345678));
+        assert_eq!(parse_swar(b"99999999"), Some(99999999));
+        assert_eq!(parse_swar(b"00000042"), Some(42));
+
+        for bad in [
+            &b""[..],
+            b"123456789",
+            b"12 4",
+            b"1/",
+            b"1:",
+            b"\xff1",
+            b"-1",
+        ] {
+            assert_eq!(parse_swar(bad), None, "{bad:?}");
+        }
+        for n in (0..100_000_000).step_by(9_871) {
+            assert_eq!(parse_swar(n.to_string().as_bytes()), Some(n));
+        }
+    }
+
+    #[test]
+    fn parses_signs() {
+        assert_eq!(parse_signed::<i32>(b"-42"), Ok(-42));
+        assert_eq!(parse_signed::<i32>(b"+42"), Ok(42));
+        assert_eq!(parse_signed::<i32>(b"42"), Ok(42));
+        assert_eq!(parse_signed::<i16>(b"-32768"), Ok(i16::MIN));
+        assert_eq!(parse_signed::<i16>(b"32768"), Err(NumberError::Overflow));
+        assert_eq!(parse_signed::<i64>(b"-9223372036854775808"), Ok(i64::MIN));
+        assert_eq!(parse_signed::<i64>(b"-"), Err(NumberError::Empty));
+        assert_eq!(
+            parse_signed::<i64>(b"-1-"),
+            Err(NumberError::InvalidDigit(2))
+        );
+        assert_eq!(parse_signed::<u32>(b"-1"), Err(NumberError::Overflow));
+    }
+}
@@ -0,0 +1,185 @@
+//! Positions and directions on a grid, so the grid days don't each need their own tables of
+//! offsets.
+
+/// A position on a grid, counted from the top left.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Point {
+    pub row: usize,
+    pub col: usize,
+}
+
+impl Point {
+    pub const fn new(row: usize, col: usize) -> Self {
+        Point { row, col }
+    }
+
+    /// Returns the point one step away in `dir`, if that's still within a `width` by `height`
+    /// area anchored at the top left.
+    pub fn step(self, dir: impl Into<Direction>, width: usize, height: usize) -> Option<Point> {
+        let (dr, dc) = dir.into().offset();
+        let row = self.row.checked_add_signed(dr).filter(|r| *r < height)?;
+        let col = self.col.checked_add_signed(dc).filter(|c| *c < width)?;
+        Some(Point { row, col })
+    }
+}
+
+/// One of the eight ways to move to a neighbouring cell.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Direction {
+    Up,
+    UpRight,
+    Right,
+    DownRight,
+    Down,
+    DownLeft,
+    Left,
+    UpLeft,
+}
+
+impl Direction {
+    /// Every direction, clockwise from up.
+    pub const ALL: [Direction; 8] = [
+        Direction::Up,
+        Direction::UpRight,
+        Direction::Right,
+        Direction::DownRight,
+        Direction::Down,
+        Direction::DownLeft,
+        Direction::Left,
+        Direction::UpLeft,
+    ];
+
+    /// The change in row and column from taking a step in this direction.
+    pub const fn offset(self) -> (isize, isize) {
+        match self {
+            Direction::Up => (-1, 0),
+            Direction::UpRight => (-1, 1),
+            Direction::Right => (0, 1),
+            Direction::DownRight => (1, 1),
+            Direction::Down => (1, 0),
+            Direction::DownLeft => (1, -1),
+            Direction::Left => (0, -1),
+            Direction::UpLeft => (-1, -1),
+        }
+    }
+
+    pub const fn is_diagonal(self) -> bool {
+        self as usize % 2 == 1
+    }
+
+    /// Turns a quarter turn clockwise, so diagonals stay diagonal.
+    pub const fn turn_right(self) -> Self {
+        Direction::ALL[(self as usize + 2) % 8]
+    }
+
+    /// Turns a quarter turn anticlockwise, so diagonals stay diagonal.
+    pub const fn turn_left(self) -> Self {
+        Direction::ALL[(self as usize + 6) % 8]
+    }
+
+    pub const fn reverse(self) -> Self {
+        Direction::ALL[(self as usize + 4) % 8]
+    }
+}
+
+/// The way something moving around the grid is facing. Unlike a [`Direction`], it can't be
+/// diagonal.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Heading {
+    Up,
+    Right,
+    Down,
+    Left,
+}
+
+impl Heading {
+    /// Every heading, clockwise from up.
+    pub const ALL: [Heading; 4] = [Heading::Up, Heading::Right, Heading::Down, Heading::Left];
+
+    /// A distinct number from 0 to 3 for each heading, handy for indexing lookup tables.
+    pub const fn index(self) -> usize {
+        self as usize
+    }
+
+    pub const fn turn_right(self) -> Self {
+        Heading::ALL[(self as usize + 1) % 4]
+    }
+
+    pub const fn turn_left(self) -> Self {
+        Heading::ALL[(self as usize + 3) % 4]
+    }
+
+    pub const fn reverse(self) -> Self {
+        Heading::ALL[(self as usize + 2) % 4]
+    }
+}
+
+impl From<Heading> for Direction {
+    fn from(heading: Heading) -> Self {
+        match heading {
+            Heading::Up => Direction::Up,
+            Heading::Right => Direction::Right,
+            Heading::Down => Direction::Down,
+            Heading::Left => Direction::Left,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn steps_within_bounds() {
+        let p = Point::new(0, 1);
+        assert_eq!(p.step(Direction::Up, 3, 2), None);
+        assert_eq!(p.step(Direction::DownLeft, 3, 2), Some(Point::new(1, 0)));
+        assert_eq!(p.step(Heading::Right, 3, 2), Some(Point::new(0, 2)));
+        assert_eq!(p.step(Heading::Right, 2, 2), None);
+        assert_eq!(Point::new(1, 0).step(Heading::Down, 3, 2), None);
+        assert_eq!(Point::new(1, 0).step(Heading::Left, 3, 2), None);
+    }
+
+    #[test]
+    fn directions_turn() {
+        use Direction::*;
+
+        assert_eq!(Up.turn_right(), Right);
+        assert_eq!(UpLeft.turn_right(), UpRight);
+        assert_eq!(Up.turn_left(), Left);
+        assert_eq!(DownRight.turn_left(), UpRight);
+        assert_eq!(Left.reverse(), Right);
+        assert_eq!(UpRight.reverse(), DownLeft);
+
+        for d in Direction::ALL {
+            assert_eq!(d.turn_right().turn_left(), d);
+            assert_eq!(d.turn_right().turn_right(), d.reverse());
+            assert_eq!(d.turn_right().is_diagonal(), d.is_diagonal());
+
+            let ((dr, dc), (rr, rc)) = (d.offset(), d.reverse().offset());
+            assert_eq!((dr + rr, dc + rc), (0, 0));
+        }
+
+        assert_eq!(Direction::ALL.iter().filter(|d| d.is_diagonal()).count(), 4);
+    }
+
+    #[test]
+    fn headings_turn() {
+        use Heading::*;
+
+        assert_eq!(Up.turn_right(), Right);
+        assert_eq!(Left.turn_right(), Up);
+        assert_eq!(Up.turn_left(), Left);
+        assert_eq!(Down.reverse(), Up);
+
+        for h in Heading::ALL {
+            assert_eq!(Heading::ALL[h.index()], h);
+            assert_eq!(
+                Direction::from(h.turn_right()),
+                Direction::from(h).turn_right()
+            );
+            assert_eq!(Direction::from(h.reverse()), Direction::from(h).reverse());
+            assert!(!Direction::from(h).is_diagonal());
+        }
+    }
+}
@@ -0,0 +1,544 @@
+//! Renders the answers and timings from a run, either for people or for scripts and dashboards.
+
+use std::{
+    fmt::Write,
+    io::{self, BufRead, Read},
+    str::FromStr,
+    time::Duration,
+};
+
+use aoc2024::day1::Explanation;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Format {
+    Text,
+    Json,
+    Csv,
+}
+
+impl FromStr for Format {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "text" => Ok(Format::Text),
+            "json" => Ok(Format::Json),
+            "csv" => Ok(Format::Csv),
+            _ => Err(()),
+        }
+    }
+}
+
+/// The outcome of solving one part of a day with a particular variant.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Record {
+    pub day: u8,
+    pub part: u8,
+    pub variant: &'static str,
+    pub answer: String,
+    pub parse_time: Duration,
+    pub solve_time: Duration,
+    pub input_hash: u64,
+}
+
+impl Record {
+    /// The time taken to parse the input and solve the part.
+    pub fn time(&self) -> Duration {
+        self.parse_time + self.solve_time
+    }
+
+    /// Whether the part took longer than `budget` to solve. Parsing is left out, as a parallel run
+    /// only parses each input once, for whichever part comes first.
+    pub fn over_budget(&self, budget: Option<Duration>) -> bool {
+        budget.is_some_and(|b| self.solve_time > b)
+    }
+}
+
+const FNV_OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+
+fn fnv1a(hash: u64, bytes: &[u8]) -> u64 {
+    bytes.iter().fold(hash, |hash, b| {
+        (hash ^ *b as u64).wrapping_mul(0x0000_0100_0000_01b3)
+    })
+}
+
+/// Hashes the input with 64-bit FNV-1a, so records from different runs can be matched up by
+/// input without storing it. Unlike std's hashers, the result is stable across releases.
+pub fn input_hash(input: &str) -> u64 {
+    fnv1a(FNV_OFFSET, input.as_bytes())
+}
+
+/// Works out the [`input_hash`] of everything read through it, for inputs that are never held in
+/// memory all at once. Trailing newlines are left out, as they're trimmed from inputs that are.
+pub struct HashingReader<R> {
+    inner: R,
+    hash: u64,
+    /// Newlines we've read but not hashed yet, as we don't know if they're trailing.
+    newlines: usize,
+}
+
+impl<R: BufRead> HashingReader<R> {
+    pub fn new(inner: R) -> Self {
+        HashingReader {
+            inner,
+            hash: FNV_OFFSET,
+            newlines: 0,
+        }
+    }
+
+    pub fn hash(&self) -> u64 {
+        self.hash
+    }
+}
+
+impl<R: BufRead> Read for HashingReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.fill_buf()?.read(buf)?;
+        self.consume(n);
+        Ok(n)
+    }
+}
+
+impl<R: BufRead> BufRead for HashingReader<R> {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        self.inner.fill_buf()
+    }
+
+    fn consume(&mut self, amt: usize) {
+        // Looking at the buffer at the end of the input would read again, which waits on a
+        // terminal.
+        if amt == 0 {
+            return;
+        }
+        // The bytes are still in the inner reader's buffer, so this doesn't read anything new.
+        if let Ok(buf) = self.inner.fill_buf() {
+            let consumed = &buf[..amt.min(buf.len())];
+            let content = consumed.iter().rposition(|b| *b != b'\n');
+            if let Some(last) = content {
+                for _ in 0..self.newlines {
+                    self.hash = fnv1a(self.hash, b"\n");
+                }
+                self.hash = fnv1a(self.hash, &consumed[..=last]);
+                self.newlines = 0;
+            }
+            self.newlines += consumed.len() - content.map_or(0, |i| i + 1);
+        }
+        self.inner.consume(amt);
+    }
+}
+
+impl Format {
+    pub fn render(&self, records: &[Record]) -> String {
+        match self {
+            Format::Text => records.iter().map(text).collect(),
+            Format::Json => json(records),
+            Format::Csv => csv(records),
+        }
+    }
+}
+
+/// Mirrors cargo-aoc's console output.
+fn text(r: &Record) -> String {
+    format!(
+        "Day {} - Part {} ({}) : {}\n\tgenerator: {:?},\n\trunner: {:?}\n\n",
+        r.day, r.part, r.variant, r.answer, r.parse_time, r.solve_time
+    )
+}
+
+fn json(records: &[Record]) -> String {
+    let mut out = String::from("[");
+
+    for (i, r) in records.iter().enumerate() {
+        let sep = if i == 0 { "\n" } else { ",\n" };
+        let _ = write!(
+            out,
+            "{sep}  {{\"day\": {}, \"part\": {}, \"variant\": {}, \"answer\": {}, \"parse_ns\": {}, \"solve_ns\": {}, \"input_hash\": \"{:016x}\"}}",
+            r.day,
+            r.part,
+            json_string(r.variant),
+            json_string(&r.answer),
+            r.parse_time.as_nanos(),
+            r.solve_time.as_nanos(),
+            r.input_hash
+        );
+    }
+
+    out.push_str(if records.is_empty() { "]\n" } else { "\n]\n" });
+    out
+}
+
+fn json_string(s: &str) -> String {
+    let mut out = String::with_capacity(s.len() + 2);
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if c.is_control() => {
+                let _ = write!(out, "\\u{:04x}", c as u32);
+            }
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+fn csv(records: &[Record]) -> String {
+    let mut out = String::from("day,part,variant,answer,parse_ns,solve_ns,input_hash\n");
+
+    for r in records {
+        let _ = writeln!(
+            out,
+            "{},{},{},{},{},{},{:016x}",
+            r.day,
+            r.part,
+            csv_field(r.variant),
+            csv_field(&r.answer),
+            r.parse_time.as_nanos(),
+            r.solve_time.as_nanos(),
+            r.input_hash
+        );
+    }
+
+    out
+}
+
+fn csv_field(s: &str) -> String {
+    if s.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", s.replace('"', "\"\""))
+    } else {
+        s.to_string()
+    }
+}
+
+/// Summarises a run of many parts: how long it took overall, how much of the work each part
+/// accounts for, slowest first, which parts took longer than the `budget` to solve, and the
+/// `errors` from those that couldn't be parsed or solved. A part's time includes parsing its input
+/// if that was done for it, but the budget is for solving alone, so every part is judged alike.
+pub fn summary(
+    records: &[Record],
+    errors: &[String],
+    wall_time: Duration,
+    budget: Option<Duration>,
+) -> String {
+    let total: Duration = records.iter().map(Record::time).sum();
+    let mut by_time = records.iter().collect::<Vec<_>>();
+    by_time.sort_by_key(|r| std::cmp::Reverse(r.time()));
+
+    let mut out = format!(
+        "{} parts in {wall_time:?} wall time, {total:?} of work\n\n{:>3}  {:>4}  {:<10}  {:>12}  {:>6}\n",
+        records.len(),
+        "day",
+        "part",
+        "variant",
+        "time",
+        "share"
+    );
+
+    for r in by_time {
+        let share = if total.is_zero() {
+            0.0
+        } else {
+            100.0 * r.time().as_secs_f64() / total.as_secs_f64()
+        };
+        let over = if r.over_budget(budget) {
+            "  over budget"
+        } else {
+            ""
+        };
+        let _ = writeln!(
+            out,
+            "{:>3}  {:>4}  {:<10}  {:>12?}  {share:>5.1}%{over}",
+            r.day,
+            r.part,
+            r.variant,
+            r.time()
+        );
+    }
+
+    if !errors.is_empty() {
+        let _ = writeln!(out, "\n{} failed", errors.len());
+        for e in errors {
+            let _ = writeln!(out, "  {e}");
+        }
+    }
+
+    out
+}
+
+/// Renders the [`Explanation`] of day 1's answers for the given parts as a table or as JSON,
+/// listing only the `top` biggest contributors to each answer if given. The two parts' tables
+/// have different columns, so there's no CSV; it's left to the caller to refuse it.
+pub fn explanation(e: &Explanation, parts: &[u8], top: Option<usize>, format: Format) -> String {
+    let pairs = top.map_or_else(|| e.pairs.clone(), |k| e.top_pairs(k));
+    let matches = top.map_or_else(|| e.matches.clone(), |k| e.top_matches(k));
+    let shown = |shown: usize, of: usize, what: &str| match top {
+        Some(_) => format!("top {shown} of {of} {what}"),
+        None => format!("{of} {what}"),
+    };
+
+    let mut out = String::new();
+
+    if format == Format::Json {
+        out.push_str("{\n  \"day\": 1");
+        if parts.contains(&1) {
+            let rows = pairs.iter().map(|p| {
+                format!(
+                    "{{\"left\": {}, \"right\": {}, \"distance\": {}}}",
+                    p.left, p.right, p.distance
+                )
+            });
+            json_part(&mut out, 1, e.distance, "pairs", rows);
+        }
+        if parts.contains(&2) {
+            let rows = matches.iter().map(|m| {
+                format!(
+                    "{{\"id\": {}, \"left\": {}, \"right\": {}, \"score\": {}}}",
+                    m.id, m.left, m.right, m.score
+                )
+            });
+            json_part(&mut out, 2, e.similarity, "matches", rows);
+        }
+        out.push_str("\n}\n");
+        return out;
+    }
+
+    if parts.contains(&1) {
+        let _ = writeln!(
+            out,
+            "Day 1 - Part 1 : {} ({})\n{:>10}  {:>10}  {:>10}",
+            e.distance,
+            shown(pairs.len(), e.pairs.len(), "pairs"),
+            "left",
+            "right",
+            "distance"
+        );
+        for p in &pairs {
+            let _ = writeln!(out, "{:>10}  {:>10}  {:>10}", p.left, p.right, p.distance);
+        }
+        out.push('\n');
+    }
+    if parts.contains(&2) {
+        let _ = writeln!(
+            out,
+            "Day 1 - Part 2 : {} ({})\n{:>10}  {:>10}  {:>10}  {:>12}",
+            e.similarity,
+            shown(matches.len(), e.matches.len(), "IDs in both lists"),
+            "id",
+            "left",
+            "right",
+            "score"
+        );
+        for m in &matches {
+            let _ = writeln!(
+                out,
+                "{:>10}  {:>10}  {:>10}  {:>12}",
+                m.id, m.left, m.right, m.score
+            );
+        }
+        out.push('\n');
+    }
+
+    out
+}
+
+/// Adds one part of an explanation to a JSON object: its answer, and a list of its rows.
+fn json_part(
+    out: &mut String,
+    part: u8,
+    answer: u64,
+    name: &str,
+    rows: impl Iterator<Item = String>,
+) {
+    let _ = write!(
+        out,
+        ",\n  \"part{part}\": {{\n    \"answer\": {answer},\n    \"{name}\": ["
+    );
+    let mut empty = true;
+    for (i, row) in rows.enumerate() {
+        let sep = if i == 0 { "\n" } else { ",\n" };
+        let _ = write!(out, "{sep}      {row}");
+        empty = false;
+    }
+    out.push_str(if empty { "]\n  }" } else { "\n    ]\n  }" });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(answer: &str) -> Record {
+        Record {
+            day: 2,
+            part: 1,
+            variant: "naive",
+            answer: answer.to_string(),
+            parse_time: Duration::from_nanos(1500),
+            solve_time: Duration::from_micros(2),
+            input_hash: 0xabc,
+        }
+    }
+
+    #[test]
+    fn hashes_with_fnv1a() {
+        assert_eq!(input_hash(""), 0xcbf2_9ce4_8422_2325);
+        assert_eq!(input_hash("a"), 0xaf63_dc4c_8601_ec8c);
+    }
+
+    #[test]
+    fn hashes_while_reading() {
+        for input in ["", "a", "ab\n\ncd", "ab\ncd\n\n\n", "\n\nab"] {
+            for capacity in 1..4 {
+                let mut reader =
+                    HashingReader::new(io::BufReader::with_capacity(capacity, input.as_bytes()));
+                io::copy(&mut reader, &mut io::sink()).unwrap();
+                assert_eq!(
+                    reader.hash(),
+                    input_hash(input.trim_end_matches('\n')),
+                    "{input:?}"
+                );
+            }
+        }
+    }
+
+    /// Reads nothing, once, and fails any read after that.
+    struct Eof(bool);
+
+    impl Read for Eof {
+        fn read(&mut self, _: &mut [u8]) -> io::Result<usize> {
+            assert!(!self.0, "read past the end");
+            self.0 = true;
+            Ok(0)
+        }
+    }
+
+    #[test]
+    fn stops_reading_at_the_end() {
+        let mut reader = HashingReader::new(io::BufReader::new(Eof(false)));
+        assert_eq!(reader.fill_buf().unwrap(), b"");
+        reader.consume(0);
+        assert_eq!(reader.hash(), input_hash(""));
+    }
+
+    #[test]
+    fn renders_json() {
+        assert_eq!(Format::Json.render(&[]), "[]\n");
+        assert_eq!(
+            Format::Json.render(&[record("2"), record("say \"hi\"\n")]),
+            r#"[
+  {"day": 2, "part": 1, "variant": "naive", "answer": "2", "parse_ns": 1500, "solve_ns": 2000, "input_hash": "0000000000000abc"},
+  {"day": 2, "part": 1, "variant": "naive", "answer": "say \"hi\"\n", "parse_ns": 1500, "solve_ns": 2000, "input_hash": "0000000000000abc"}
+]
+"#
+        );
+    }
+
+    #[test]
+    fn renders_csv() {
+        assert_eq!(
+            Format::Csv.render(&[record("2"), record("a,\"b\"")]),
+            "day,part,variant,answer,parse_ns,solve_ns,input_hash
+2,1,naive,2,1500,2000,0000000000000abc
+2,1,naive,\"a,\"\"b\"\"\",1500,2000,0000000000000abc
+"
+        );
+    }
+
+    #[test]
+    fn summarises_slowest_first() {
+        let mut slow = record("1");
+        slow.day = 6;
+        slow.solve_time = Duration::from_micros(6);
+
+        assert_eq!(
+            summary(
+                &[record("2"), slow],
+                &[],
+                Duration::from_micros(7),
+                Some(Duration::from_micros(5))
+            ),
+            "2 parts in 7µs wall time, 11µs of work
+
+day  part  variant             time   share
+  6     1  naive              7.5µs   68.2%  over budget
+  2     1  naive              3.5µs   31.8%
+"
+        );
+
+        // Only solving counts against the budget, as a part isn't always parsed for.
+        let r = record("2");
+        assert!(!r.over_budget(Some(Duration::from_micros(3))));
+        assert!(r.over_budget(Some(Duration::from_nanos(1999))));
+        assert!(!r.over_budget(None));
+    }
+
+    #[test]
+    fn summarises_failures() {
+        let errors = ["day 5 (default): bad input on line 3".to_string()];
+        assert_eq!(
+            summary(&[record("2")], &errors, Duration::from_micros(4), None),
+            "1 parts in 4µs wall time, 3.5µs of work
+
+day  part  variant             time   share
+  2     1  naive              3.5µs  100.0%
+
+1 failed
+  day 5 (default): bad input on line 3
+"
+        );
+    }
+
+    fn explanation() -> Explanation {
+        aoc2024::day1::explain("3   4\n4   3\n2   5\n1   3\n3   9\n3   3").unwrap()
+    }
+
+    #[test]
+    fn explains_as_a_table() {
+        assert_eq!(
+            super::explanation(&explanation(), &[1, 2], Some(2), Format::Text),
+            "Day 1 - Part 1 : 11 (top 2 of 6 pairs)
+      left       right    distance
+         4           9           5
+         1           3           2
+
+Day 1 - Part 2 : 31 (top 2 of 2 IDs in both lists)
+        id        left       right         score
+         3           3           3            27
+         4           1           1             4
+
+"
+        );
+    }
+
+    #[test]
+    fn explains_as_json() {
+        assert_eq!(
+            super::explanation(&explanation(), &[2], None, Format::Json),
+            r#"{
+  "day": 1,
+  "part2": {
+    "answer": 31,
+    "matches": [
+      {"id": 3, "left": 3, "right": 3, "score": 27},
+      {"id": 4, "left": 1, "right": 1, "score": 4}
+    ]
+  }
+}
+"#
+        );
+        assert!(super::explanation(&explanation(), &[1], Some(0), Format::Json)
+            .contains("\"pairs\": []"));
+    }
+
+    #[test]
+    fn parses_formats() {
+        assert_eq!("csv".parse(), Ok(Format::Csv));
+        assert_eq!("json".parse(), Ok(Format::Json));
+        assert_eq!("text".parse(), Ok(Format::Text));
+        assert_eq!("xml".parse::<Format>(), Err(()));
+    }
+}
@@ -0,0 +1,87 @@
+//! Sorting for the small unsigned numbers found in puzzle inputs, where a radix sort beats
+//! comparing them.
+
+/// How many bits of the key each pass of [`radix_sort`] sorts by.
+const RADIX_BITS: u32 = 11;
+
+const BUCKETS: usize = 1 << RADIX_BITS;
+
+/// Sorts `values` with an LSD radix sort, [`RADIX_BITS`] bits at a time, starting from the lowest.
+/// Only the bits up to the largest value are looked at, so 5 digit numbers take two passes.
+pub fn radix_sort(values: &mut [u32]) {
+    let Some(&max) = values.iter().max() else {
+        return;
+    };
+    let passes = (u32::BITS - max.leading_zeros()).div_ceil(RADIX_BITS);
+
+    let mut scratch = vec![0; values.len()];
+    let (mut from, mut to) = (&mut *values, &mut scratch[..]);
+
+    for pass in 0..passes {
+        let shift = pass * RADIX_BITS;
+        let bucket = |v: u32| (v >> shift) as usize % BUCKETS;
+
+        // Count each bucket, then turn the counts into where each bucket starts.
+        let mut starts = [0; BUCKETS];
+        for v in from.iter() {
+            starts[bucket(*v)] += 1;
+        }
+        let mut total = 0;
+        for start in &mut starts {
+            (*start, total) = (total, total + *start);
+        }
+
+        // Each pass is stable, so values keep their order from the passes before.
+        for v in from.iter() {
+            let start = &mut starts[bucket(*v)];
+            to[*start] = *v;
+            *start += 1;
+        }
+        (from, to) = (to, from);
+    }
+
+    // After an odd number of passes, the sorted values are in the scratch space.
+    if passes % 2 == 1 {
+        to.copy_from_slice(from);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use proptest::prelude::*;
+
+    use super::*;
+
+    #[test]
+    fn sorts_by_every_bit() {
+        let mut values = [3, 0, u32::MAX, 99_999, 2048, 2047, 1 << 22, 3];
+        radix_sort(&mut values);
+        assert_eq!(values, [0, 3, 3, 2047, 2048, 99_999, 1 << 22, u32::MAX]);
+
+        let mut empty: [u32; 0] = [];
+        radix_sort(&mut empty);
+        let mut zeros = [0; 3];
+        radix_sort(&mut zeros);
+        assert_eq!(zeros, [0; 3]);
+    }
+
+    proptest! {
+        #[test]
+        fn sorts_like_sort_unstable(mut values: Vec<u32>) {
+            let mut expected = values.clone();
+            expected.sort_unstable();
+            radix_sort(&mut values);
+            prop_assert_eq!(values, expected);
+        }
+
+        #[test]
+        fn sorts_small_keys_like_sort_unstable(
+            mut values in prop::collection::vec(0..100_000_u32, 0..2000),
+        ) {
+            let mut expected = values.clone();
+            expected.sort_unstable();
+            radix_sort(&mut values);
+            prop_assert_eq!(values, expected);
+        }
+    }
+}